@@ -1,10 +1,22 @@
+use bevy::utils::HashMap;
 use bevy_asset_loader::{
     asset_collection::AssetCollection,
     loading_state::{config::ConfigureLoadingState, LoadingState, LoadingStateAppExt},
+    mapped::AssetFileStem,
 };
 use bevy_spine::{Atlas, SkeletonData, SkeletonJson};
 
-use crate::{game::demon::DemonBrainDef, prelude::*};
+use crate::{
+    game::{
+        audio::SoundCueDefs,
+        backdrop::DeskLayout,
+        demon::{DemonBrainDef, DistractionDefs, Grammar},
+        input::InputMapDef,
+        quests::QuestDefs,
+        shop::ShopDefs,
+    },
+    prelude::*,
+};
 
 pub struct GameAssetsPlugin;
 
@@ -16,14 +28,47 @@ impl Plugin for GameAssetsPlugin {
                 .load_collection::<GameAssets>(),
         )
         .add_plugins(bevy_common_assets::ron::RonAssetPlugin::<DemonBrainDef>::new(&["brain"]))
+        .add_plugins(
+            bevy_common_assets::ron::RonAssetPlugin::<DistractionDefs>::new(&["distractions"]),
+        )
+        .add_plugins(bevy_common_assets::ron::RonAssetPlugin::<InputMapDef>::new(
+            &["bindings"],
+        ))
+        .add_plugins(bevy_common_assets::ron::RonAssetPlugin::<Grammar>::new(&[
+            "grammar",
+        ]))
+        .add_plugins(bevy_common_assets::ron::RonAssetPlugin::<SoundCueDefs>::new(&["cues"]))
+        .add_plugins(bevy_common_assets::ron::RonAssetPlugin::<QuestDefs>::new(
+            &["quests"],
+        ))
+        .add_plugins(bevy_common_assets::ron::RonAssetPlugin::<ShopDefs>::new(&[
+            "shop",
+        ]))
+        .add_plugins(bevy_common_assets::ron::RonAssetPlugin::<DeskLayout>::new(
+            &["desk"],
+        ))
         .add_systems(OnExit(Loading), create_skeletons);
     }
 }
 
 #[derive(AssetCollection, Resource)]
 pub struct GameAssets {
-    #[asset(path = "demon.brain")]
-    pub demon_brain: Handle<DemonBrainDef>,
+    #[asset(path = "brains", collection(typed, mapped))]
+    pub demon_brains: HashMap<AssetFileStem, Handle<DemonBrainDef>>,
+    #[asset(path = "desks", collection(typed, mapped))]
+    pub desk_layouts: HashMap<AssetFileStem, Handle<DeskLayout>>,
+    #[asset(path = "demon.distractions")]
+    pub distractions: Handle<DistractionDefs>,
+    #[asset(path = "demon.grammar")]
+    pub grammar: Handle<Grammar>,
+    #[asset(path = "sounds.cues")]
+    pub sound_cues: Handle<SoundCueDefs>,
+    #[asset(path = "desk.quests")]
+    pub quests: Handle<QuestDefs>,
+    #[asset(path = "desk.shop")]
+    pub shop: Handle<ShopDefs>,
+    #[asset(path = "controls.bindings")]
+    pub input_map: Handle<InputMapDef>,
     #[asset(path = "Debug.png")]
     pub debug_texture: Handle<Image>,
     #[asset(path = "spines/context.atlas")]
@@ -44,6 +89,26 @@ pub struct GameAssets {
     pub chat_json: Handle<SkeletonJson>,
 }
 
+impl GameAssets {
+    // Sorted, so the listing doesn't depend on load order.
+    pub fn brain_archetypes(&self) -> Vec<(String, Handle<DemonBrainDef>)> {
+        let mut archetypes: Vec<(String, Handle<DemonBrainDef>)> = self
+            .demon_brains
+            .iter()
+            .map(|(name, handle)| (name.to_string(), handle.clone()))
+            .collect();
+        archetypes.sort_by(|(a, _), (b, _)| a.cmp(b));
+        archetypes
+    }
+
+    pub fn desk_layout(&self, name: &str) -> Option<Handle<DeskLayout>> {
+        self.desk_layouts
+            .iter()
+            .find(|(stem, _)| stem.to_string() == name)
+            .map(|(_, handle)| handle.clone())
+    }
+}
+
 #[derive(Resource)]
 pub struct Skeletons {
     pub context: Handle<SkeletonData>,
@@ -0,0 +1,62 @@
+use std::{fs, process::ExitCode};
+
+use LudumDare56::game::demon::{babble_wav, random_genes, DemonDna};
+
+const DEFAULT_OUT: &str = "babble.wav";
+
+fn parse_dna(hex: &str) -> Option<DemonDna> {
+    if hex.len() != 32 || !hex.is_ascii() {
+        return None;
+    }
+    let mut genes = [0; 16];
+    for (idx, gene) in genes.iter_mut().enumerate() {
+        *gene = u8::from_str_radix(&hex[idx * 2..idx * 2 + 2], 16).ok()?;
+    }
+    Some(DemonDna(genes))
+}
+
+/// Writes a demon's gibberish voice for a line of text, to check how it sounds.
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("Usage: babble [--dna HEX] [--nonce N] [--out FILE] TEXT...");
+        println!("Writes the voice to {} unless --out is given.", DEFAULT_OUT);
+        return ExitCode::SUCCESS;
+    }
+    let mut take_flag = |flag: &str| -> Option<String> {
+        let idx = args.iter().position(|arg| arg == flag)?;
+        if idx + 1 >= args.len() {
+            return None;
+        }
+        let value = args.remove(idx + 1);
+        args.remove(idx);
+        Some(value)
+    };
+    let dna = match take_flag("--dna") {
+        Some(hex) => match parse_dna(&hex) {
+            Some(dna) => dna,
+            None => {
+                eprintln!("--dna needs 32 hex digits");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => random_genes(),
+    };
+    let nonce = take_flag("--nonce")
+        .and_then(|nonce| nonce.parse().ok())
+        .unwrap_or(0);
+    let out = take_flag("--out").unwrap_or(DEFAULT_OUT.to_string());
+    let text = args.join(" ");
+
+    let hex: String = dna.0.iter().map(|gene| format!("{:02x}", gene)).collect();
+    match fs::write(&out, babble_wav(&dna, nonce, &text)) {
+        Ok(_) => {
+            println!("Wrote {} saying \"{}\" to {}", hex, text, out);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to write {}: {}", out, err);
+            ExitCode::FAILURE
+        }
+    }
+}
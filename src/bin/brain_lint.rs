@@ -0,0 +1,134 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use LudumDare56::game::demon::{
+    lint_brain, validate_distractions, DemonBrainDef, DistractionDefs, Distractions, LintLevel,
+};
+
+const EXIT_CLEAN: u8 = 0;
+const EXIT_WARNINGS: u8 = 1;
+const EXIT_ERRORS: u8 = 2;
+const EXIT_USAGE: u8 = 3;
+
+const DEFAULT_BRAINS: &str = "assets/brains";
+const DEFAULT_DISTRACTIONS: &str = "assets/demon.distractions";
+
+fn collect_brains(path: &Path, brains: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if path.is_dir() {
+        let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() || entry.extension().map(|ext| ext == "brain").unwrap_or(false) {
+                collect_brains(&entry, brains)?;
+            }
+        }
+    } else {
+        brains.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Checks demon brain files. Exits 0 when clean, 1 on warnings, 2 on errors
+/// (including parse failures) and 3 when the arguments or files can't be read.
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("Usage: brain_lint [--distractions FILE] [FILE_OR_DIR]...");
+        println!("Checks .brain files, defaulting to {}.", DEFAULT_BRAINS);
+        return ExitCode::from(EXIT_CLEAN);
+    }
+    let mut distractions_path = DEFAULT_DISTRACTIONS.to_string();
+    if let Some(idx) = args.iter().position(|arg| arg == "--distractions") {
+        if idx + 1 >= args.len() {
+            eprintln!("--distractions needs a file");
+            return ExitCode::from(EXIT_USAGE);
+        }
+        distractions_path = args.remove(idx + 1);
+        args.remove(idx);
+    }
+    let roots = if args.is_empty() {
+        vec![DEFAULT_BRAINS.to_string()]
+    } else {
+        args
+    };
+
+    let mut exit = EXIT_CLEAN;
+    let source = match fs::read_to_string(&distractions_path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{}: {}", distractions_path, err);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    let distractions = match ron::de::from_str::<DistractionDefs>(&source) {
+        Ok(defs) => {
+            for error in validate_distractions(&defs) {
+                println!("{}: error: {}", distractions_path, error);
+                exit = EXIT_ERRORS;
+            }
+            Distractions::from_defs(&defs)
+        }
+        Err(err) => {
+            println!(
+                "{}:{}:{}: error: {}",
+                distractions_path, err.position.line, err.position.col, err.code
+            );
+            return ExitCode::from(EXIT_ERRORS);
+        }
+    };
+
+    let mut brains = Vec::new();
+    for root in roots.iter() {
+        if let Err(err) = collect_brains(Path::new(root), &mut brains) {
+            eprintln!("{}: {}", root, err);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    }
+
+    for brain in brains.iter() {
+        let source = match fs::read_to_string(brain) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("{}: {}", brain.display(), err);
+                return ExitCode::from(EXIT_USAGE);
+            }
+        };
+        let def: DemonBrainDef = match ron::de::from_str(&source) {
+            Ok(def) => def,
+            Err(err) => {
+                println!(
+                    "{}:{}:{}: error: {}",
+                    brain.display(),
+                    err.position.line,
+                    err.position.col,
+                    err.code
+                );
+                exit = exit.max(EXIT_ERRORS);
+                continue;
+            }
+        };
+        for lint in lint_brain(&def, &distractions) {
+            let (level, code) = match lint.level {
+                LintLevel::Warning => ("warning", EXIT_WARNINGS),
+                LintLevel::Error => ("error", EXIT_ERRORS),
+            };
+            println!(
+                "{}: {}: {} ({})",
+                brain.display(),
+                level,
+                lint.message,
+                lint.path
+            );
+            exit = exit.max(code);
+        }
+    }
+    if exit == EXIT_CLEAN {
+        println!("{} brain(s) look fine.", brains.len());
+    }
+    ExitCode::from(exit)
+}
@@ -0,0 +1,226 @@
+use bevy::{audio::Volume, utils::HashMap};
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+// How far above the desk a fully zoomed out camera "hears" from, in world units.
+const HEARING_HEIGHT: f32 = 300.0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum CueTrigger {
+    Game,             // Only played when the game sends a SoundEvent by name.
+    Event(String),    // A Spine event, like "Summon".
+    Start(String),    // A Spine animation starting, like "walk".
+    Complete(String), // A Spine animation finishing, once per loop.
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SoundCueDef {
+    pub name: String,
+    pub trigger: CueTrigger,
+    pub files: Vec<String>,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    // Distance from the camera at which the cue can no longer be heard.
+    pub falloff: f32,
+    #[serde(default)]
+    pub offset: Vec2,
+    #[serde(default)]
+    pub pitch_jitter: f32,
+    #[serde(default)]
+    pub cooldown: f32,
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct SoundCueDefs {
+    pub cues: Vec<SoundCueDef>,
+}
+
+pub struct SoundCue {
+    pub def: SoundCueDef,
+    pub sources: Vec<Handle<AudioSource>>,
+}
+
+#[derive(Resource, Default)]
+pub struct SoundCues {
+    cues: HashMap<String, SoundCue>,
+    last_played: HashMap<String, f32>,
+    plays: usize,
+}
+
+impl SoundCues {
+    pub fn get(&self, name: &str) -> Option<&SoundCue> {
+        self.cues.get(name)
+    }
+
+    fn triggered_by(&self, trigger: &CueTrigger) -> Vec<String> {
+        self.cues
+            .values()
+            .filter(|cue| cue.def.trigger == *trigger)
+            .map(|cue| cue.def.name.clone())
+            .collect()
+    }
+}
+
+pub fn validate_sound_cues(defs: &SoundCueDefs) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut seen = Vec::new();
+    for def in defs.cues.iter() {
+        if seen.contains(&&def.name) {
+            errors.push(format!("{} is defined twice", def.name));
+        }
+        seen.push(&def.name);
+        if def.files.is_empty() {
+            errors.push(format!("{} has no files", def.name));
+        }
+        if def.falloff <= 0.0 {
+            errors.push(format!("{} needs a positive falloff", def.name));
+        }
+    }
+    errors
+}
+
+// Something that should make a noise. `at` is in world space, or None to play at full volume.
+#[derive(Event, Debug, Clone)]
+pub struct SoundEvent {
+    pub cue: String,
+    pub at: Option<Vec2>,
+}
+
+impl SoundEvent {
+    pub fn at(cue: &str, at: Vec2) -> Self {
+        Self {
+            cue: cue.to_string(),
+            at: Some(at),
+        }
+    }
+}
+
+pub fn sync_sound_cues(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<SoundCueDefs>>,
+    defs: Res<Assets<SoundCueDefs>>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                if let Some(defs) = defs.get(*id) {
+                    println!("Loading {} sound cues", defs.cues.len());
+                    for error in validate_sound_cues(defs) {
+                        println!("Bad sound cue {}", error);
+                    }
+                    let cues = defs
+                        .cues
+                        .iter()
+                        .map(|def| {
+                            let cue = SoundCue {
+                                def: def.clone(),
+                                sources: def
+                                    .files
+                                    .iter()
+                                    .map(|file| asset_server.load(file.clone()))
+                                    .collect(),
+                            };
+                            (def.name.clone(), cue)
+                        })
+                        .collect();
+                    commands.insert_resource(SoundCues {
+                        cues,
+                        ..Default::default()
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Turns Spine events and animations into sound cues at the skeleton's position.
+pub fn cue_spine_sounds(
+    mut spine_events: EventReader<SpineEvent>,
+    mut sound_events: EventWriter<SoundEvent>,
+    cues: Res<SoundCues>,
+    globals: Query<&GlobalTransform>,
+) {
+    for event in spine_events.read() {
+        let (entity, trigger) = match event {
+            SpineEvent::Event { entity, name, .. } => (entity, CueTrigger::Event(name.clone())),
+            SpineEvent::Start { entity, animation } => {
+                (entity, CueTrigger::Start(animation.clone()))
+            }
+            SpineEvent::Complete {
+                entity, animation, ..
+            } => (entity, CueTrigger::Complete(animation.clone())),
+            _ => continue,
+        };
+        let position = globals
+            .get(*entity)
+            .map(|global| global.translation().truncate())
+            .ok();
+        for cue in cues.triggered_by(&trigger) {
+            sound_events.send(SoundEvent { cue, at: position });
+        }
+    }
+}
+
+pub fn play_sounds(
+    mut commands: Commands,
+    mut sound_events: EventReader<SoundEvent>,
+    mut cues: ResMut<SoundCues>,
+    time: Res<Time<Real>>,
+    camera: Query<(&GlobalTransform, &OrthographicProjection), With<Camera2d>>,
+) {
+    let listener = camera
+        .iter()
+        .next()
+        .map(|(global, projection)| {
+            (
+                global.translation().truncate(),
+                projection.scale * HEARING_HEIGHT,
+            )
+        })
+        .unwrap_or((Vec2::ZERO, HEARING_HEIGHT));
+    let now = time.elapsed_seconds();
+    for event in sound_events.read() {
+        let cue = cues.get(&event.cue);
+        if cue.is_none() {
+            println!("No sound cue named {}", event.cue);
+            continue;
+        }
+        let cue = cue.unwrap();
+        if cue.sources.is_empty() {
+            continue;
+        }
+        let last = cues.last_played.get(&event.cue).copied();
+        if last.is_some_and(|last| now - last < cue.def.cooldown) {
+            continue;
+        }
+        // Zooming out lifts the listener away from the desk, so everything gets a bit quieter.
+        let volume = match event.at {
+            Some(at) => {
+                let (ear, height) = listener;
+                let distance = (at + cue.def.offset - ear).extend(height).length();
+                cue.def.volume * (1.0 - distance / cue.def.falloff).clamp(0.0, 1.0)
+            }
+            None => cue.def.volume,
+        };
+        if volume <= 0.01 {
+            continue;
+        }
+        let source = cue.sources[cues.plays % cue.sources.len()].clone();
+        let speed = 1.0 + (rand::random::<f32>() - 0.5) * 2.0 * cue.def.pitch_jitter;
+        commands.spawn(AudioBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN
+                .with_volume(Volume::new(volume))
+                .with_speed(speed),
+        });
+        cues.plays += 1;
+        cues.last_played.insert(event.cue.clone(), now);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{
+    game::{spawn_main_chat_box, Inventory, Lantern, Upgrades},
+    prelude::*,
+};
+
+use super::InRoom;
+
+// Seconds a fresh candle burns for.
+const BURN_TIME: f32 = 120.0;
+const AMBIENT_LIGHT: f32 = 0.2;
+const DESK_CANDLE_LIGHT: f32 = 0.16;
+const DOORWAY_CANDLE_LIGHT: f32 = 0.1;
+const LANTERN_LIGHT: f32 = 0.15;
+const MAX_DARKNESS: f32 = 0.55;
+
+// The wax of a candle, from 1 for a fresh one down to 0 when it's spent.
+#[derive(Component, Debug, Clone)]
+pub struct Candle {
+    pub item: DeskItem,
+    pub wax: f32,
+}
+
+impl Candle {
+    pub fn is_spent(&self) -> bool {
+        self.wax <= 0.0
+    }
+
+    pub fn is_doorway(&self) -> bool {
+        matches!(self.item, DeskItem::DoorwayCandle(_))
+    }
+}
+
+// The flame. `lit_order` counts up each time any candle is lit, so the first lit sorts first.
+#[derive(Component, Debug, Clone)]
+pub struct Wick {
+    pub bone: String,
+    pub lit: bool,
+    pub lit_order: Option<u32>,
+}
+
+impl Wick {
+    pub fn snuff(&mut self) {
+        self.lit = false;
+        self.lit_order = None;
+    }
+}
+
+// How lit a room is, from 0 for pitch black to 1 with every candle burning. Lives on the desk.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DeskLight(pub f32);
+
+impl Default for DeskLight {
+    fn default() -> Self {
+        Self(AMBIENT_LIGHT)
+    }
+}
+
+impl DeskLight {
+    // Some distractions happen more in the dark, by their own darkness scale at pitch black.
+    pub fn distraction_scale(&self, darkness_scale: f32) -> f32 {
+        1.0 + (1.0 - self.0) * darkness_scale
+    }
+
+    // Tools are slow going when you can't see what you're doing.
+    pub fn work_speed(&self) -> f32 {
+        0.4 + self.0 * 0.6
+    }
+}
+
+pub fn next_light_order<'a>(wicks: impl Iterator<Item = &'a Wick>) -> u32 {
+    wicks
+        .filter_map(|wick| wick.lit_order)
+        .max()
+        .map(|order| order + 1)
+        .unwrap_or(0)
+}
+
+// A spent candle has to be swapped for a spare before it'll light again.
+pub fn light_wick(
+    commands: &mut Commands,
+    candle: &mut Candle,
+    wick: &mut Wick,
+    order: u32,
+    inventory: &mut Inventory,
+) -> bool {
+    if wick.lit {
+        return true;
+    }
+    if candle.is_spent() {
+        if inventory.spare_candles == 0 {
+            spawn_main_chat_box(
+                commands,
+                "info",
+                "That candle is spent, and there are no [term]spare candles[/term] left."
+                    .to_string(),
+            );
+            return false;
+        }
+        inventory.spare_candles -= 1;
+        candle.wax = 1.0;
+    }
+    wick.lit = true;
+    wick.lit_order = Some(order);
+    true
+}
+
+#[derive(Component)]
+pub struct Darkness;
+
+// A child of the desk, so each room is only as dark as its own candles leave it.
+pub fn darkness_bundle(size: Vec2) -> impl Bundle {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(0.0, 0.0, 0.05, 0.0),
+                custom_size: Some(size),
+                ..Default::default()
+            },
+            // Over the desk and demons, under their chat boxes.
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 6.0)),
+            ..Default::default()
+        },
+        Darkness,
+    )
+}
+
+pub fn burn_candles(
+    time: Res<Time>,
+    upgrades: Res<Upgrades>,
+    mut rooms: Query<(Entity, &mut DeskLight)>,
+    mut candles: Query<(&mut Candle, &mut Wick, &InRoom)>,
+    lanterns: Query<&InRoom, With<Lantern>>,
+) {
+    let burn_time = BURN_TIME * upgrades.burn_time_scale();
+    for (room, mut light) in rooms.iter_mut() {
+        let lantern_count = lanterns.iter().filter(|lantern| lantern.0 == room).count();
+        let mut level = AMBIENT_LIGHT + lantern_count as f32 * LANTERN_LIGHT;
+        for (mut candle, mut wick, in_room) in candles.iter_mut() {
+            if in_room.0 != room || !wick.lit {
+                continue;
+            }
+            candle.wax -= time.delta_seconds() / burn_time;
+            if candle.is_spent() {
+                candle.wax = 0.0;
+                println!("{:?} burnt out", candle.item);
+                wick.snuff();
+                continue;
+            }
+            level += if candle.is_doorway() {
+                DOORWAY_CANDLE_LIGHT
+            } else {
+                DESK_CANDLE_LIGHT
+            };
+        }
+        let level = level.min(1.0);
+        if light.0 != level {
+            light.0 = level;
+        }
+    }
+}
+
+// Flames are drawn by scaling their bones, so this is the only place that touches them.
+pub fn sync_candle_bones(wicks: Query<(&Wick, &InRoom)>, mut desks: Query<&mut Spine, With<Desk>>) {
+    for (wick, room) in wicks.iter() {
+        let desk = desks.get_mut(room.0);
+        if desk.is_err() {
+            continue;
+        }
+        let mut desk = desk.unwrap();
+        let scale = if wick.lit { 1.0 } else { 0.0 };
+        if let Some(mut bone) = desk.skeleton.find_bone_mut(&wick.bone) {
+            if bone.scale_x() != scale {
+                bone.set_scale(Vec2::new(scale, scale));
+            }
+        }
+    }
+}
+
+pub fn show_darkness(
+    rooms: Query<&DeskLight, Changed<DeskLight>>,
+    mut darkness: Query<(&Parent, &mut Sprite), With<Darkness>>,
+) {
+    for (parent, mut sprite) in darkness.iter_mut() {
+        if let Ok(light) = rooms.get(parent.get()) {
+            sprite.color.set_alpha((1.0 - light.0) * MAX_DARKNESS);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::{system::RunSystemOnce, world::CommandQueue};
+
+    use super::*;
+
+    fn burning_room(wax: f32) -> (World, Entity, Entity) {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Upgrades>();
+        let room = world.spawn(DeskLight::default()).id();
+        let candle = world
+            .spawn((
+                Candle {
+                    item: DeskItem::Candle(0),
+                    wax,
+                },
+                Wick {
+                    bone: "candle0".to_string(),
+                    lit: true,
+                    lit_order: Some(0),
+                },
+                InRoom(room),
+            ))
+            .id();
+        (world, room, candle)
+    }
+
+    fn burn_for(world: &mut World, seconds: f32) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        world.run_system_once(burn_candles);
+    }
+
+    #[test]
+    fn lit_candle_burns_and_lights_the_room() {
+        let (mut world, room, candle) = burning_room(1.0);
+        burn_for(&mut world, 1.0);
+        assert!(world.get::<Candle>(candle).unwrap().wax < 1.0);
+        assert!(world.get::<Wick>(candle).unwrap().lit);
+        let light = world.get::<DeskLight>(room).unwrap().0;
+        assert!((light - (AMBIENT_LIGHT + DESK_CANDLE_LIGHT)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn candle_burning_down_snuffs_the_wick() {
+        let (mut world, room, candle) = burning_room(0.01);
+        burn_for(&mut world, BURN_TIME);
+        let spent = world.get::<Candle>(candle).unwrap();
+        assert_eq!(spent.wax, 0.0);
+        assert!(spent.is_spent());
+        let wick = world.get::<Wick>(candle).unwrap();
+        assert!(!wick.lit);
+        assert_eq!(wick.lit_order, None);
+        assert_eq!(world.get::<DeskLight>(room).unwrap().0, AMBIENT_LIGHT);
+    }
+
+    #[test]
+    fn spent_candle_takes_a_spare() {
+        let world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let mut inventory = Inventory::default();
+        inventory.spare_candles = 1;
+        let mut candle = Candle {
+            item: DeskItem::Candle(0),
+            wax: 0.0,
+        };
+        let mut wick = Wick {
+            bone: "candle0".to_string(),
+            lit: false,
+            lit_order: None,
+        };
+        assert!(light_wick(
+            &mut commands,
+            &mut candle,
+            &mut wick,
+            3,
+            &mut inventory
+        ));
+        assert_eq!(inventory.spare_candles, 0);
+        assert_eq!(candle.wax, 1.0);
+        assert!(wick.lit);
+        assert_eq!(wick.lit_order, Some(3));
+    }
+
+    #[test]
+    fn spent_candle_without_spares_stays_dark() {
+        let world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let mut inventory = Inventory::default();
+        inventory.spare_candles = 0;
+        let mut candle = Candle {
+            item: DeskItem::Candle(0),
+            wax: 0.0,
+        };
+        let mut wick = Wick {
+            bone: "candle0".to_string(),
+            lit: false,
+            lit_order: None,
+        };
+        assert!(!light_wick(
+            &mut commands,
+            &mut candle,
+            &mut wick,
+            0,
+            &mut inventory
+        ));
+        assert!(candle.is_spent());
+        assert!(!wick.lit);
+    }
+
+    #[test]
+    fn lit_order_counts_up_past_the_latest_flame() {
+        let wick = |lit_order: Option<u32>| Wick {
+            bone: String::new(),
+            lit: lit_order.is_some(),
+            lit_order,
+        };
+        assert_eq!(next_light_order([].iter()), 0);
+        let mut wicks = vec![wick(Some(0)), wick(None), wick(Some(3))];
+        assert_eq!(next_light_order(wicks.iter()), 4);
+        // Snuffing the first one doesn't let the next flame sort before the others.
+        wicks[0].snuff();
+        assert_eq!(next_light_order(wicks.iter()), 4);
+        wicks[2].snuff();
+        assert_eq!(next_light_order(wicks.iter()), 0);
+    }
+}
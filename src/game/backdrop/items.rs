@@ -4,12 +4,15 @@ use serde::{Deserialize, Serialize};
 use crate::{
     game::{
         get_lore, get_name, get_potion, spawn_demon, spawn_main_chat_box, AttachedChatBox,
-        DemonBrainDef, DemonDna,
+        DemonBrainDef, DemonDna, Inventory, ProductKind, ProductUsed, SoundEvent, UseProduct,
     },
     prelude::*,
 };
 
-use super::Desk;
+use super::{
+    light_wick, next_light_order, Candle, CurrentRoom, Desk, InRoom, Travelling, Wick,
+    WorkCompletion, Workstation, WorkstationAppExt,
+};
 
 const CANDLE_0_TRACK: usize = 0;
 const CANDLE_1_TRACK: usize = 1;
@@ -21,7 +24,7 @@ const ALEMBIC_TRACK: usize = 6;
 const SUMMONING_TRACK: usize = 7;
 const DOORWAY_TRACK: usize = 8;
 
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DeskItem {
     Alembic,
     Summoning,
@@ -39,6 +42,49 @@ impl DeskItem {
             DeskItem::Potion | DeskItem::Candle(_) | DeskItem::DoorwayCandle(_) => false,
         }
     }
+
+    pub fn interactable(&self) -> Interactable {
+        match self {
+            DeskItem::Candle(idx) => Interactable::Candle(*idx),
+            DeskItem::DoorwayCandle(idx) => Interactable::DoorwayCandle(*idx),
+            DeskItem::Potion => Interactable::Potion,
+            DeskItem::Journal => Interactable::Journal,
+            DeskItem::Doorway => Interactable::Doorway,
+            // item => Interactable::Contextable(Contextable::DeskItem(item)),
+            _ => Interactable::Backdrop,
+        }
+    }
+}
+
+pub fn register_desk_workstations(app: &mut App) {
+    app.register_workstation(
+        DeskItem::Alembic,
+        Workstation {
+            track: ALEMBIC_TRACK,
+            work_animation: "demon_alembic",
+            product: Some(ProductKind::Potion),
+            on_complete: None,
+        },
+    )
+    .register_workstation(
+        DeskItem::Journal,
+        Workstation {
+            track: JOURNAL_TRACK,
+            work_animation: "demon_journal",
+            product: Some(ProductKind::LorePage),
+            on_complete: None,
+        },
+    )
+    .register_workstation(
+        DeskItem::Doorway,
+        Workstation {
+            track: DOORWAY_TRACK,
+            work_animation: "demon_doorway",
+            // The card is left behind when the demon leaves for good, see travel_through_doorways.
+            product: None,
+            on_complete: Some(leave_through_doorway),
+        },
+    );
 }
 
 #[derive(Component, Default, Clone, Debug)]
@@ -46,7 +92,6 @@ pub struct DeskItemState {
     pub user: Option<Entity>,
     pub progress: f32,
     pub just_completed: Option<DemonDna>,
-    pub completed: Vec<DemonDna>,
 }
 
 // Summoning
@@ -54,42 +99,28 @@ pub fn handle_summoning_context(
     mut commands: Commands,
     skeletons: Res<Skeletons>,
     mut context_events: EventReader<ContextAction>,
-    mut desk: Query<&mut Spine, With<Desk>>,
+    mut inventory: ResMut<Inventory>,
+    mut candles: Query<(&mut Candle, &mut Wick, &InRoom)>,
     attached_chats: Query<&AttachedChatBox>,
+    current: Res<CurrentRoom>,
 ) {
-    let desk = desk.iter_mut().next();
-    if desk.is_none() {
-        return;
-    }
-    let mut desk = desk.unwrap();
     for event in context_events.read() {
-        match event {
-            ContextAction::PressLeft(Contextable::DeskItem(DeskItem::Summoning)) => {
-                desk.skeleton
-                    .find_bone_mut("candle0")
-                    .unwrap()
-                    .set_scale(Vec2::new(1., 1.));
-                desk.skeleton
-                    .find_bone_mut("candle1")
-                    .unwrap()
-                    .set_scale(Vec2::new(1., 1.));
-            }
-            ContextAction::PressRight(Contextable::DeskItem(DeskItem::Summoning)) => {
-                desk.skeleton
-                    .find_bone_mut("candle2")
-                    .unwrap()
-                    .set_scale(Vec2::new(1., 1.));
-                desk.skeleton
-                    .find_bone_mut("candle3")
-                    .unwrap()
-                    .set_scale(Vec2::new(1., 1.));
-            }
-            ContextAction::PressMiddle(Contextable::DeskItem(DeskItem::Summoning)) => {
-                desk.skeleton
-                    .find_bone_mut("candle4")
-                    .unwrap()
-                    .set_scale(Vec2::new(1., 1.));
+        let lighting: &[usize] = match event {
+            ContextAction::PressLeft(Contextable::DeskItem(DeskItem::Summoning)) => &[0, 1],
+            ContextAction::PressRight(Contextable::DeskItem(DeskItem::Summoning)) => &[2, 3],
+            ContextAction::PressMiddle(Contextable::DeskItem(DeskItem::Summoning)) => &[4],
+            _ => &[],
+        };
+        for idx in lighting {
+            let order = next_light_order(candles.iter().map(|(_, wick, _)| wick));
+            // The menu is for the summoning circle in the room on screen.
+            if let Some((mut candle, mut wick, _)) = candles.iter_mut().find(|(candle, _, room)| {
+                candle.item == DeskItem::Candle(*idx) && Some(room.0) == current.0
+            }) {
+                light_wick(&mut commands, &mut candle, &mut wick, order, &mut inventory);
             }
+        }
+        match event {
             ContextAction::HoverLeft(chat_attach, Contextable::DeskItem(DeskItem::Summoning))
             | ContextAction::HoverMiddle(chat_attach, Contextable::DeskItem(DeskItem::Summoning))
             | ContextAction::HoverRight(chat_attach, Contextable::DeskItem(DeskItem::Summoning)) => {
@@ -103,8 +134,13 @@ pub fn handle_summoning_context(
                 );
             }
             ContextAction::Unhover(chat_attach) => {
-                if let Ok(attached_chat) = attached_chats.get(*chat_attach) {
-                    commands.entity(attached_chat.0).despawn_recursive();
+                // The box may have already dismissed itself.
+                if let Some(chat) = attached_chats
+                    .get(*chat_attach)
+                    .ok()
+                    .and_then(|attached_chat| commands.get_entity(attached_chat.0))
+                {
+                    chat.despawn_recursive();
                 }
             }
             _ => continue,
@@ -113,51 +149,29 @@ pub fn handle_summoning_context(
 }
 
 pub fn light_candle(
+    mut commands: Commands,
     mut interact_events: EventReader<InteractEvent>,
-    mut desk: Query<&mut Spine, With<Desk>>,
+    mut sound_events: EventWriter<SoundEvent>,
+    mut inventory: ResMut<Inventory>,
+    mut candles: Query<(&mut Candle, &mut Wick)>,
 ) {
-    let desk = desk.iter_mut().next();
-    if desk.is_none() {
-        return;
-    }
-    let mut desk = desk.unwrap();
     for event in interact_events.read() {
-        if let InteractEvent {
-            interact_type: InteractType::Press,
-            interactable: Interactable::Candle(idx),
-            ..
-        } = event
-        {
-            let bone_name = match idx {
-                0 => "candle0",
-                1 => "candle1",
-                2 => "candle2",
-                3 => "candle3",
-                4 => "candle4",
-                _ => continue,
-            };
-            let mut bone = desk.skeleton.find_bone_mut(bone_name).unwrap();
-            if bone.scale_x() == 0.0 {
-                bone.set_scale(Vec2::new(1., 1.));
-            } else {
-                bone.set_scale(Vec2::new(0., 0.));
-            }
-        } else if let InteractEvent {
-            interact_type: InteractType::Press,
-            interactable: Interactable::DoorwayCandle(idx),
-            ..
-        } = event
-        {
-            let bone_name = match idx {
-                0 => "doorway_candle_wick0",
-                1 => "doorway_candle_wick1",
-                _ => continue,
-            };
-            let mut bone = desk.skeleton.find_bone_mut(bone_name).unwrap();
-            if bone.scale_x() == 0.0 {
-                bone.set_scale(Vec2::new(1., 1.));
-            } else {
-                bone.set_scale(Vec2::new(0., 0.));
+        if event.interact_type != InteractType::Press {
+            continue;
+        }
+        if !matches!(
+            event.interactable,
+            Interactable::Candle(_) | Interactable::DoorwayCandle(_)
+        ) {
+            continue;
+        }
+        let order = next_light_order(candles.iter().map(|(_, wick)| wick));
+        // The pressed candle, not its namesake in another room.
+        if let Ok((mut candle, mut wick)) = candles.get_mut(event.entity) {
+            if wick.lit {
+                wick.snuff();
+            } else if light_wick(&mut commands, &mut candle, &mut wick, order, &mut inventory) {
+                sound_events.send(SoundEvent::at("candle_lit", event.mouse_world_location));
             }
         }
     }
@@ -166,29 +180,22 @@ pub fn light_candle(
 pub fn trigger_summoning(
     mut commands: Commands,
     mut animation_events: EventReader<SpineEvent>,
-    mut desk: Query<&mut Spine, With<Desk>>,
-    items: Query<(&DeskItem, &SpineBone, &Transform)>,
+    mut desk: Query<(Entity, &mut Spine), With<Desk>>,
+    items: Query<(&DeskItem, &InRoom, &GlobalTransform)>,
+    mut candles: Query<(&Candle, &mut Wick, &InRoom)>,
     skeletons: Res<Skeletons>,
     game_assets: Res<GameAssets>,
     brains: Res<Assets<DemonBrainDef>>,
 ) {
-    {
-        let desk = desk.iter_mut().next();
-        if desk.is_none() {
-            return;
-        }
-        let mut desk = desk.unwrap();
-        let mut lit_count = 0;
-        ["candle0", "candle1", "candle2", "candle3", "candle4"]
+    for (room, mut desk) in desk.iter_mut() {
+        // Every candle on the desk has to be burning, however many the layout has.
+        let (lit, total) = candles
             .iter()
-            .for_each(|bone_name| {
-                if let Some(bone) = desk.skeleton.find_bone(bone_name) {
-                    if bone.scale_x() == 1.0 {
-                        lit_count += 1;
-                    }
-                }
+            .filter(|(candle, _, in_room)| !candle.is_doorway() && in_room.0 == room)
+            .fold((0, 0), |(lit, total), (_, wick, _)| {
+                (lit + wick.lit as usize, total + 1)
             });
-        if lit_count == 5 {
+        if total > 0 && lit == total {
             if desk
                 .animation_state
                 .get_current(SUMMONING_TRACK)
@@ -213,11 +220,11 @@ pub fn trigger_summoning(
     }
     for event in animation_events.read() {
         if let SpineEvent::Event { entity, name, .. } = event {
-            if let Ok(mut desk) = desk.get_mut(*entity) {
+            if desk.contains(*entity) {
                 if *name == "Summon" {
-                    if let Some(location) = items.iter().find_map(|(item, _, transform)| {
-                        if let DeskItem::Summoning = item {
-                            Some(transform.translation)
+                    if let Some(location) = items.iter().find_map(|(item, room, transform)| {
+                        if *item == DeskItem::Summoning && room.0 == *entity {
+                            Some(transform.translation())
                         } else {
                             None
                         }
@@ -231,28 +238,13 @@ pub fn trigger_summoning(
                             None,
                             &brains,
                         );
-                        desk.skeleton
-                            .find_bone_mut("candle0")
-                            .unwrap()
-                            .set_scale(Vec2::new(0., 0.));
-                        desk.skeleton
-                            .find_bone_mut("candle1")
-                            .unwrap()
-                            .set_scale(Vec2::new(0., 0.));
-                        desk.skeleton
-                            .find_bone_mut("candle2")
-                            .unwrap()
-                            .set_scale(Vec2::new(0., 0.));
-                        desk.skeleton
-                            .find_bone_mut("candle3")
-                            .unwrap()
-                            .set_scale(Vec2::new(0., 0.));
-                        desk.skeleton
-                            .find_bone_mut("candle4")
-                            .unwrap()
-                            .set_scale(Vec2::new(0., 0.));
+                        for (candle, mut wick, room) in candles.iter_mut() {
+                            if !candle.is_doorway() && room.0 == *entity {
+                                wick.snuff();
+                            }
+                        }
                     } else {
-                        panic!("Failed to find summoning location");
+                        println!("Desk layout has nowhere to summon to");
                     }
                 }
             }
@@ -263,29 +255,21 @@ pub fn trigger_summoning(
 pub fn trigger_doorway_summoning(
     mut commands: Commands,
     mut animation_events: EventReader<SpineEvent>,
-    mut desk: Query<&mut Spine, With<Desk>>,
-    items: Query<(&DeskItem, &SpineBone, &Transform)>,
+    mut desk: Query<(Entity, &mut Spine), With<Desk>>,
+    items: Query<(&DeskItem, &InRoom, &GlobalTransform)>,
+    mut candles: Query<(&Candle, &mut Wick, &InRoom)>,
     skeletons: Res<Skeletons>,
     game_assets: Res<GameAssets>,
     brains: Res<Assets<DemonBrainDef>>,
 ) {
-    {
-        let desk = desk.iter_mut().next();
-        if desk.is_none() {
-            return;
-        }
-        let mut desk = desk.unwrap();
-        let mut lit_count = 0;
-        ["doorway_candle_wick0", "doorway_candle_wick1"]
+    for (room, mut desk) in desk.iter_mut() {
+        let (lit, total) = candles
             .iter()
-            .for_each(|bone_name| {
-                if let Some(bone) = desk.skeleton.find_bone(bone_name) {
-                    if bone.scale_x() == 1.0 {
-                        lit_count += 1;
-                    }
-                }
+            .filter(|(candle, _, in_room)| candle.is_doorway() && in_room.0 == room)
+            .fold((0, 0), |(lit, total), (_, wick, _)| {
+                (lit + wick.lit as usize, total + 1)
             });
-        if lit_count == 2 {
+        if total > 0 && lit == total {
             if desk
                 .animation_state
                 .get_current(DOORWAY_TRACK)
@@ -310,11 +294,11 @@ pub fn trigger_doorway_summoning(
     }
     for event in animation_events.read() {
         if let SpineEvent::Event { entity, name, .. } = event {
-            if let Ok(mut desk) = desk.get_mut(*entity) {
+            if desk.contains(*entity) {
                 if *name == "SummonDoorway" {
-                    if let Some(location) = items.iter().find_map(|(item, _, transform)| {
-                        if let DeskItem::Doorway = item {
-                            Some(transform.translation)
+                    if let Some(location) = items.iter().find_map(|(item, room, transform)| {
+                        if *item == DeskItem::Doorway && room.0 == *entity {
+                            Some(transform.translation())
                         } else {
                             None
                         }
@@ -340,27 +324,21 @@ pub fn trigger_doorway_summoning(
                                 Some(dna),
                                 &brains,
                             );
-                            desk.skeleton
-                                .find_bone_mut("doorway_candle_wick0")
-                                .unwrap()
-                                .set_scale(Vec2::new(0., 0.));
-                            desk.skeleton
-                                .find_bone_mut("doorway_candle_wick1")
-                                .unwrap()
-                                .set_scale(Vec2::new(0., 0.));
+                            for (candle, mut wick, room) in candles.iter_mut() {
+                                if candle.is_doorway() && room.0 == *entity {
+                                    wick.snuff();
+                                }
+                            }
                         } else {
                             println!("No one to summon");
-                            desk.skeleton
-                                .find_bone_mut("doorway_candle_wick0")
-                                .unwrap()
-                                .set_scale(Vec2::new(0., 0.));
-                            desk.skeleton
-                                .find_bone_mut("doorway_candle_wick1")
-                                .unwrap()
-                                .set_scale(Vec2::new(0., 0.));
+                            for (candle, mut wick, room) in candles.iter_mut() {
+                                if candle.is_doorway() && room.0 == *entity {
+                                    wick.snuff();
+                                }
+                            }
                         }
                     } else {
-                        panic!("Failed to find summoning location");
+                        println!("Desk layout has nowhere to summon to");
                     }
                 }
             }
@@ -369,231 +347,129 @@ pub fn trigger_doorway_summoning(
 }
 
 // Alembic
-pub fn trigger_alembic(
-    mut desk: Query<&mut Spine, With<Desk>>,
-    mut items: Query<(&DeskItem, &mut DeskItemState)>,
-) {
-    let desk = desk.iter_mut().next();
-    if desk.is_none() {
-        return;
-    }
-    let mut desk = desk.unwrap();
-    let dripped = if let Some((alembic, mut state)) = items
-        .iter_mut()
-        .find(|(item, _)| matches!(item, DeskItem::Alembic))
-    {
-        if state.user.is_some() {
-            if get_current_animation(&desk, JOURNAL_TRACK).is_none() {
-                desk.animation_state
-                    .set_animation_by_name(ALEMBIC_TRACK, "demon_alembic", true);
-            }
-        } else {
-            desk.animation_state.set_empty_animation(ALEMBIC_TRACK, 0.);
-        }
-        if let Some(dna) = state.just_completed {
-            state.just_completed = None;
-            state.progress = 0.0;
-            state.user = None;
-            Some(dna)
-        } else {
-            None
-        }
+pub fn show_potion_water(inventory: Res<Inventory>, mut desks: Query<&mut Spine, With<Desk>>) {
+    let scale = if inventory.count(ProductKind::Potion) > 0 {
+        1.
     } else {
-        None
+        0.
     };
-    if let Some(dna) = dripped {
+    // The shelf is shared, so every room's alembic shows it.
+    for mut desk in desks.iter_mut() {
         if let Some(mut bone) = desk.skeleton.find_bone_mut("water") {
-            bone.set_scale(Vec2::new(1., 1.));
+            bone.set_scale(Vec2::new(scale, scale));
         }
-        items.iter_mut().for_each(|(item, mut state)| {
-            if let DeskItem::Potion = item {
-                state.completed.push(dna);
-            }
-        });
     }
 }
 
 pub fn drink_potion(
     mut commands: Commands,
-    main_chat: Query<(Entity, &MainChatAttach)>,
-    skeletons: Res<Skeletons>,
-    mut interact_events: EventReader<InteractEvent>,
-    mut desk: Query<&mut Spine, With<Desk>>,
-    mut items: Query<(&DeskItem, &mut DeskItemState)>,
+    mut use_events: EventReader<UseProduct>,
+    mut inventory: ResMut<Inventory>,
+    mut used_events: EventWriter<ProductUsed>,
 ) {
-    let desk = desk.iter_mut().next();
-    if desk.is_none() {
-        return;
-    }
-    let mut desk = desk.unwrap();
-    let potion = items
-        .iter_mut()
-        .find(|(item, _)| matches!(item, DeskItem::Potion));
-    if potion.is_none() {
-        return;
-    }
-    let (_, mut potion) = potion.unwrap();
-    for event in interact_events.read() {
-        if let InteractEvent {
-            interact_type: InteractType::Press,
-            interactable: Interactable::Potion,
-            ..
-        } = event
-        {
-            if let Some(potion_dna) = potion.completed.pop() {
-                if let Some(mut bone) = desk.skeleton.find_bone_mut("water") {
-                    bone.set_scale(Vec2::new(0., 0.));
-                    spawn_main_chat_box(
-                        &mut commands,
-                        &main_chat,
-                        &skeletons,
-                        "info",
-                        get_potion(&potion_dna),
-                    );
-                }
-            }
+    for UseProduct(id) in use_events.read() {
+        if inventory.get(*id).map(|product| product.kind) != Some(ProductKind::Potion) {
+            continue;
+        }
+        if let Some(potion) = inventory.take(*id) {
+            used_events.send(ProductUsed(potion.clone()));
+            spawn_main_chat_box(&mut commands, "info", get_potion(&potion.maker));
         }
     }
 }
 
 // Journal
-pub fn trigger_journal(
-    mut desk: Query<&mut Spine, With<Desk>>,
-    mut items: Query<(&DeskItem, &mut DeskItemState)>,
+pub fn read_page(
+    mut commands: Commands,
+    mut use_events: EventReader<UseProduct>,
+    mut inventory: ResMut<Inventory>,
+    mut used_events: EventWriter<ProductUsed>,
 ) {
-    let desk = desk.iter_mut().next();
-    if desk.is_none() {
-        return;
-    }
-    let mut desk = desk.unwrap();
-    if let Some((alembic, mut state)) = items
-        .iter_mut()
-        .find(|(item, _)| matches!(item, DeskItem::Journal))
-    {
-        if state.user.is_some() {
-            if get_current_animation(&desk, JOURNAL_TRACK).is_none() {
-                desk.animation_state
-                    .set_animation_by_name(JOURNAL_TRACK, "demon_journal", true);
-            }
-        } else {
-            desk.animation_state.set_empty_animation(JOURNAL_TRACK, 0.);
+    for UseProduct(id) in use_events.read() {
+        if inventory.get(*id).map(|product| product.kind) != Some(ProductKind::LorePage) {
+            continue;
         }
-        if let Some(dna) = state.just_completed {
-            state.completed.push(dna);
-            state.just_completed = None;
-            state.progress = 0.0;
-            state.user = None;
+        if let Some(page) = inventory.take(*id) {
+            used_events.send(ProductUsed(page.clone()));
+            let text = get_lore(&page.maker);
+            spawn_main_chat_box(&mut commands, "info", text);
         }
     }
 }
 
-pub fn read_page(
-    mut commands: Commands,
-    main_chat: Query<(Entity, &MainChatAttach)>,
-    skeletons: Res<Skeletons>,
-    mut interact_events: EventReader<InteractEvent>,
-    mut items: Query<(&DeskItem, &mut DeskItemState)>,
-) {
-    let journal = items
-        .iter_mut()
-        .find(|(item, _)| matches!(item, DeskItem::Journal));
-    if journal.is_none() {
-        return;
-    }
-    let (_, mut journal) = journal.unwrap();
-    for event in interact_events.read() {
-        if let InteractEvent {
-            interact_type: InteractType::Press,
-            interactable: Interactable::Journal,
-            ..
-        } = event
-        {
-            if let Some(dna) = journal.completed.pop() {
-                let text = get_lore(&dna);
-                spawn_main_chat_box(&mut commands, &main_chat, &skeletons, "info", text);
-            }
-        }
+// Doorway
+fn leave_through_doorway(commands: &mut Commands, _desk: &mut Spine, completion: &WorkCompletion) {
+    if let Some(mut leaving) = completion
+        .user
+        .and_then(|entity| commands.get_entity(entity))
+    {
+        leaving.insert(Travelling {
+            from: completion.room,
+        });
     }
 }
 
-// Doorway
-pub fn trigger_doorway(
-    mut desk: Query<&mut Spine, With<Desk>>,
-    mut items: Query<(&DeskItem, &mut DeskItemState)>,
+pub fn summon_from_card(
     mut commands: Commands,
-    demons: Query<(Entity, &Demon)>,
+    mut drop_events: EventReader<DropEvent>,
+    mut inventory: ResMut<Inventory>,
+    mut used_events: EventWriter<ProductUsed>,
+    items: Query<(&DeskItem, &GlobalTransform)>,
+    skeletons: Res<Skeletons>,
+    game_assets: Res<GameAssets>,
+    brains: Res<Assets<DemonBrainDef>>,
 ) {
-    let desk = desk.iter_mut().next();
-    if desk.is_none() {
-        return;
-    }
-    let mut desk = desk.unwrap();
-    if let Some((alembic, mut state)) = items
-        .iter_mut()
-        .find(|(item, _)| matches!(item, DeskItem::Doorway))
-    {
-        if state.user.is_some() {
-            if get_current_animation(&desk, DOORWAY_TRACK).is_none() {
-                desk.animation_state
-                    .set_animation_by_name(DOORWAY_TRACK, "demon_doorway", true);
-            }
-        } else {
-            desk.animation_state.set_empty_animation(JOURNAL_TRACK, 0.);
+    for event in drop_events.read() {
+        let location = event
+            .target
+            .and_then(|target| items.get(target).ok())
+            .filter(|(item, _)| **item == DeskItem::Doorway)
+            .map(|(_, transform)| transform.translation());
+        let is_card = event
+            .product
+            .and_then(|id| inventory.get(id))
+            .map(|product| product.kind == ProductKind::CallingCard)
+            .unwrap_or(false);
+        if location.is_none() || !is_card {
+            continue;
         }
-        if let Some(dna) = state.just_completed {
-            state.completed.push(dna);
-            state.just_completed = None;
-            state.progress = 0.0;
-            state.user = None;
-            let despawned =
-                demons.iter().find_map(
-                    |(entity, demon)| {
-                        if demon.dna == dna {
-                            Some(entity)
-                        } else {
-                            None
-                        }
-                    },
-                );
-            if let Some(despawned) = despawned.and_then(|entity| commands.get_entity(entity)) {
-                despawned.despawn_recursive();
-            }
+        if let Some(card) = event.product.and_then(|id| inventory.take(id)) {
+            used_events.send(ProductUsed(card.clone()));
+            println!("Summoning {} from their card", get_name(&card.maker));
+            spawn_demon(
+                &mut commands,
+                &game_assets,
+                skeletons.demon.clone(),
+                location.unwrap().truncate(),
+                Some(card.maker),
+                &brains,
+            );
         }
     }
 }
 
 pub fn read_card(
     mut commands: Commands,
-    main_chat: Query<(Entity, &MainChatAttach)>,
-    skeletons: Res<Skeletons>,
-    mut interact_events: EventReader<InteractEvent>,
-    mut items: Query<(&DeskItem, &mut DeskItemState)>,
+    mut use_events: EventReader<UseProduct>,
+    mut inventory: ResMut<Inventory>,
+    mut used_events: EventWriter<ProductUsed>,
 ) {
-    let journal = items
-        .iter_mut()
-        .find(|(item, _)| matches!(item, DeskItem::Doorway));
-    if journal.is_none() {
-        return;
-    }
-    let (_, mut journal) = journal.unwrap();
-    for event in interact_events.read() {
-        if let InteractEvent {
-            interact_type: InteractType::Press,
-            interactable: Interactable::Doorway,
-            ..
-        } = event
-        {
-            if let Some(dna) = journal.completed.pop() {
-                let name = get_name(&dna);
-                spawn_main_chat_box(
-                    &mut commands,
-                    &main_chat,
-                    &skeletons,
-                    "info",
-                    format!("{}'s calling card is burned into your mind.", name),
-                );
-                CursedConfig::discord().store_cursed_bytes(&dna.0, &name);
-            }
+    for UseProduct(id) in use_events.read() {
+        if inventory.get(*id).map(|product| product.kind) != Some(ProductKind::CallingCard) {
+            continue;
+        }
+        if let Some(card) = inventory.take(*id) {
+            used_events.send(ProductUsed(card.clone()));
+            let name = get_name(&card.maker);
+            spawn_main_chat_box(
+                &mut commands,
+                "info",
+                format!(
+                    "[name]{}[/name]'s calling card is burned into your mind.",
+                    name
+                ),
+            );
+            CursedConfig::discord().store_cursed_bytes(&card.maker.0, &name);
         }
     }
 }
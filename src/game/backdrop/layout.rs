@@ -0,0 +1,82 @@
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Placement {
+    Bone(String), // Follows a bone in the desk rig, art and all.
+    // Moves a rig bone, relative to its parent bone, so its art moves with it.
+    MovedBone(String, Vec2),
+    At(Vec2), // A spot on the desk with no bone, so no art of its own.
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum InteractShape {
+    Slot(String), // A bounding box attachment in the desk rig.
+    Circle(f32),
+    Rect(Vec2),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LayoutItem {
+    pub item: DeskItem,
+    pub at: Placement,
+    // Where demons have to stand to use the item.
+    #[serde(default)]
+    pub shape: Option<InteractShape>,
+    // Overrides whether demons can be sent to the item.
+    #[serde(default)]
+    pub demon_usable: Option<bool>,
+    // The bone scaled to show a candle's flame, for candles.
+    #[serde(default)]
+    pub wick: Option<String>,
+}
+
+impl LayoutItem {
+    pub fn demon_usable(&self) -> bool {
+        self.demon_usable
+            .unwrap_or_else(|| self.item.demon_can_use())
+    }
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+pub struct DeskLayout {
+    pub items: Vec<LayoutItem>,
+}
+
+// Marks desk items demons may be sent to.
+#[derive(Component)]
+pub struct DemonUsable;
+
+pub fn validate_desk_layout(layout: &DeskLayout, spine: &Spine) -> Vec<String> {
+    let mut errors = Vec::new();
+    let has_bone = |name: &str| spine.skeleton.find_bone(name).is_some();
+    for (idx, entry) in layout.items.iter().enumerate() {
+        if layout.items[..idx]
+            .iter()
+            .any(|other| other.item == entry.item)
+        {
+            errors.push(format!("{:?} is placed twice", entry.item));
+        }
+        match &entry.at {
+            Placement::Bone(bone) | Placement::MovedBone(bone, _) if !has_bone(bone) => {
+                errors.push(format!("{:?} is on missing bone {}", entry.item, bone));
+            }
+            _ => {}
+        }
+        if let Some(InteractShape::Slot(slot)) = &entry.shape {
+            if spine.skeleton.find_slot(slot).is_none() {
+                errors.push(format!("{:?} uses missing slot {}", entry.item, slot));
+            }
+        }
+        let candle = matches!(entry.item, DeskItem::Candle(_) | DeskItem::DoorwayCandle(_));
+        match &entry.wick {
+            Some(wick) if !has_bone(wick) => {
+                errors.push(format!("{:?} has missing wick bone {}", entry.item, wick));
+            }
+            None if candle => errors.push(format!("{:?} has no wick", entry.item)),
+            _ => {}
+        }
+    }
+    errors
+}
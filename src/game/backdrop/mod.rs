@@ -2,3 +2,11 @@ mod setup;
 pub use setup::*;
 mod items;
 pub use items::*;
+mod workstation;
+pub use workstation::*;
+mod candles;
+pub use candles::*;
+mod layout;
+pub use layout::*;
+mod rooms;
+pub use rooms::*;
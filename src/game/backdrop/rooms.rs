@@ -0,0 +1,204 @@
+use crate::{
+    game::{spawn_main_chat_box, CameraRig, Inventory, ProductKind},
+    prelude::*,
+};
+
+use super::JobCompleted;
+
+// Layouts for each room, in the order the doorway leads through them.
+pub const ROOMS: [&str; 2] = ["study", "cellar"];
+// Rooms sit side by side in the world, far enough apart to never see each other.
+pub const ROOM_SPACING: f32 = 1600.0;
+// Seconds a demon ignores the doorway it just came out of.
+pub const ARRIVAL_GRACE: f32 = 10.0;
+// How far into the room from the doorway a demon steps on arrival.
+const ARRIVAL_STEP: f32 = 80.0;
+
+// The desk something belongs to.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InRoom(pub Entity);
+
+// The room the camera is looking at.
+#[derive(Resource, Debug, Default)]
+pub struct CurrentRoom(pub Option<Entity>);
+
+// A demon on its way through the doorway, set by the doorway when the job is done.
+#[derive(Component, Debug)]
+pub struct Travelling {
+    pub from: Entity,
+}
+
+// Where a demon has been, so it goes home once it has seen every room instead of looping.
+#[derive(Component, Debug)]
+pub struct Travelled {
+    pub rooms: Vec<Entity>,
+    pub arrived_at: f32,
+}
+
+impl Travelled {
+    pub fn just_arrived(&self, now: f32) -> bool {
+        now - self.arrived_at < ARRIVAL_GRACE
+    }
+}
+
+// Rooms sorted by their place in ROOMS, which is also left to right.
+fn sorted_rooms<'a>(desks: impl Iterator<Item = (Entity, &'a Transform)>) -> Vec<Entity> {
+    let mut rooms: Vec<(Entity, f32)> = desks
+        .map(|(entity, transform)| (entity, transform.translation.x))
+        .collect();
+    rooms.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    rooms.into_iter().map(|(entity, _)| entity).collect()
+}
+
+fn next_room(rooms: &[Entity], room: Entity) -> Option<Entity> {
+    let idx = rooms.iter().position(|other| *other == room)?;
+    Some(rooms[(idx + 1) % rooms.len()])
+}
+
+// New demons belong to whichever desk they turned up on.
+pub fn assign_rooms(
+    mut commands: Commands,
+    demons: Query<(Entity, &Transform), (With<Demon>, Without<InRoom>)>,
+    desks: Query<(Entity, &Transform), With<Desk>>,
+) {
+    for (entity, transform) in demons.iter() {
+        let nearest = desks.iter().min_by(|(_, a), (_, b)| {
+            let a = a
+                .translation
+                .truncate()
+                .distance(transform.translation.truncate());
+            let b = b
+                .translation
+                .truncate()
+                .distance(transform.translation.truncate());
+            a.total_cmp(&b)
+        });
+        if let Some((desk, _)) = nearest {
+            commands.entity(entity).insert(InRoom(desk));
+        }
+    }
+}
+
+pub fn switch_rooms(
+    actions: Res<ActionState>,
+    mut current: ResMut<CurrentRoom>,
+    desks: Query<(Entity, &Transform), With<Desk>>,
+    followed: Query<&InRoom>,
+    mut rigs: Query<&mut CameraRig>,
+) {
+    let rooms = sorted_rooms(desks.iter());
+    if rooms.is_empty() {
+        return;
+    }
+    if current.0.is_none() {
+        current.0 = Some(rooms[0]);
+    }
+    let room = current.0.unwrap();
+    for mut rig in rigs.iter_mut() {
+        if actions.just_pressed(InputAction::NextRoom) {
+            let next = next_room(&rooms, room).unwrap_or(rooms[0]);
+            if let Ok((_, transform)) = desks.get(next) {
+                rig.target = transform.translation.truncate();
+                rig.follow = None;
+            }
+            current.0 = Some(next);
+        } else if let Some(InRoom(followed_room)) =
+            rig.follow.and_then(|entity| followed.get(entity).ok())
+        {
+            // Following a demon through the doorway takes the camera along.
+            if *followed_room != room {
+                current.0 = Some(*followed_room);
+            }
+        }
+    }
+}
+
+// Demons that went through a doorway come out of the next room's. Once there's nowhere new to
+// go they leave for good, and only then leave their calling card behind.
+pub fn travel_through_doorways(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut inventory: ResMut<Inventory>,
+    mut job_events: EventWriter<JobCompleted>,
+    mut travellers: Query<
+        (
+            Entity,
+            &Travelling,
+            Option<&Travelled>,
+            &mut Demon,
+            &mut Transform,
+            &mut Velocity,
+        ),
+        Without<Desk>,
+    >,
+    desks: Query<(Entity, &Transform, &Desk), Without<Demon>>,
+    items: Query<(&DeskItem, &InRoom, &GlobalTransform)>,
+) {
+    let rooms = sorted_rooms(
+        desks
+            .iter()
+            .map(|(entity, transform, _)| (entity, transform)),
+    );
+    for (entity, travelling, travelled, mut demon, mut transform, mut velocity) in
+        travellers.iter_mut()
+    {
+        let mut visited = travelled
+            .map(|travelled| travelled.rooms.clone())
+            .unwrap_or_else(|| vec![travelling.from]);
+        let destination = next_room(&rooms, travelling.from).filter(|room| !visited.contains(room));
+        if destination.is_none() {
+            println!("Demon left through the doorway");
+            inventory.add(ProductKind::CallingCard, demon.dna, time.elapsed_seconds());
+            job_events.send(JobCompleted {
+                item: DeskItem::Doorway,
+                product: ProductKind::CallingCard,
+                maker: demon.dna,
+            });
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let destination = destination.unwrap();
+        let center = desks
+            .get(destination)
+            .map(|(_, desk_transform, _)| desk_transform.translation.truncate());
+        if center.is_err() {
+            continue;
+        }
+        let center = center.unwrap();
+        let doorway = items.iter().find_map(|(item, room, global)| {
+            if *item == DeskItem::Doorway && room.0 == destination {
+                Some(global.translation().truncate())
+            } else {
+                None
+            }
+        });
+        // Step out of the doorway towards the middle of the desk, or just turn up there.
+        let arrival = doorway
+            .map(|doorway| doorway + (center - doorway).normalize_or_zero() * ARRIVAL_STEP)
+            .unwrap_or(center);
+        transform.translation = arrival.extend(transform.translation.z);
+        velocity.linvel = Vec2::ZERO;
+        demon.in_area_for_tool = None;
+        demon.assigned_tool = None;
+        demon.action = DemonController::Idle;
+        visited.push(destination);
+        commands.entity(entity).remove::<Travelling>().insert((
+            InRoom(destination),
+            Travelled {
+                rooms: visited,
+                arrived_at: time.elapsed_seconds(),
+            },
+        ));
+        if let Ok((_, _, desk)) = desks.get(destination) {
+            println!("Demon travelled to the {}", desk.layout);
+            spawn_main_chat_box(
+                &mut commands,
+                "info",
+                format!(
+                    "A demon wandered through to the [term]{}[/term].",
+                    desk.layout
+                ),
+            );
+        }
+    }
+}
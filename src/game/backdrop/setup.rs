@@ -3,6 +3,11 @@ use crate::{
     prelude::*,
 };
 
+use super::{
+    darkness_bundle, validate_desk_layout, Candle, CurrentRoom, DemonUsable, DeskLayout, DeskLight,
+    InRoom, InteractShape, Placement, Wick, ROOMS, ROOM_SPACING,
+};
+
 pub const BASE_DESK_WIDTH: f32 = 512.0;
 pub const BASE_DESK_HEIGHT: f32 = 512.0;
 
@@ -11,38 +16,54 @@ pub struct Desk {
     pub width: f32,
     pub height: f32,
     pub boundaries: Option<()>,
+    pub layout: String, // Name of the layout file in assets/desks.
 }
 
 pub fn spawn_desk(mut commands: Commands, game_assets: Res<GameAssets>, skeletons: Res<Skeletons>) {
-    let transform = Transform::from_translation(Vec3::new(0.0, 0.0, -1.0));
-    commands.spawn((
-        SpineBundle {
-            skeleton: skeletons.desk.clone(),
-            transform,
-            ..Default::default()
-        },
-        Desk {
-            width: BASE_DESK_WIDTH,
-            height: BASE_DESK_HEIGHT,
-            boundaries: None,
-        },
-    ));
+    for (idx, layout) in ROOMS.iter().enumerate() {
+        let position = Vec3::new(idx as f32 * ROOM_SPACING, 0.0, -1.0);
+        let desk = commands
+            .spawn((
+                SpineBundle {
+                    skeleton: skeletons.desk.clone(),
+                    transform: Transform::from_translation(position),
+                    ..Default::default()
+                },
+                Desk {
+                    width: BASE_DESK_WIDTH,
+                    height: BASE_DESK_HEIGHT,
+                    boundaries: None,
+                    layout: layout.to_string(),
+                },
+                DeskLight::default(),
+            ))
+            .with_children(|desk| {
+                desk.spawn(darkness_bundle(Vec2::splat(ROOM_SPACING)));
+            })
+            .id();
+        if idx == 0 {
+            commands.insert_resource(CurrentRoom(Some(desk)));
+        }
+    }
 }
 
-fn get_polygon_for_bounding_box(spine: &Spine, slot: &'static str) -> Collider {
-    let slot = spine
-        .skeleton
-        .find_slot(slot)
-        .expect("Missing boundaries slot");
-    let box_attachment = slot
-        .bounding_box_attachment()
-        .expect("Missing boundaries box");
+fn get_polygon_for_bounding_box(spine: &Spine, slot: &str) -> Option<Collider> {
+    let slot = spine.skeleton.find_slot(slot)?;
+    let box_attachment = slot.bounding_box_attachment()?;
     let vertices: Vec<Vec2> = box_attachment
         .vertices2()
         .iter()
         .map(|v| Vec2::new(v.x, v.y))
         .collect();
-    Collider::convex_hull(vertices.as_slice()).unwrap()
+    Collider::convex_hull(vertices.as_slice())
+}
+
+fn get_collider_for_shape(spine: &Spine, shape: &InteractShape) -> Option<Collider> {
+    match shape {
+        InteractShape::Slot(slot) => get_polygon_for_bounding_box(spine, slot),
+        InteractShape::Circle(radius) => Some(Collider::ball(*radius)),
+        InteractShape::Rect(size) => Some(Collider::cuboid(size.x / 2.0, size.y / 2.0)),
+    }
 }
 
 fn get_polyline_from_boundaries(spine: &Spine) -> Collider {
@@ -66,6 +87,8 @@ pub fn initialize_desk(
     mut readies: EventReader<SpineReadyEvent>,
     mut query: Query<(&mut Desk, &mut Spine)>,
     mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    layouts: Res<Assets<DeskLayout>>,
 ) {
     // Setup new menus.
     for event in readies.read() {
@@ -76,48 +99,73 @@ pub fn initialize_desk(
                 .entity(event.entity)
                 .insert((get_polyline_from_boundaries(&spine), RigidBody::Fixed));
 
-            for bone_name in [
-                "candle0", "candle1", "candle2", "candle3", "candle4", "water", "line0", "line1",
-                "line2", "line3", "line4",
-            ]
-            .iter()
-            {
+            for bone_name in ["water", "line0", "line1", "line2", "line3", "line4"].iter() {
                 let mut bone = spine.skeleton.find_bone_mut(*bone_name).unwrap();
                 bone.set_scale(Vec2::new(0., 0.));
             }
 
-            for (bone_name, bone) in event.bones.iter() {
-                println!("Adding context item: {}", bone_name);
-                let (item, bounding_slot) = match bone_name.as_str() {
-                    "alembic" => (DeskItem::Alembic, Some("alembic_interact")),
-                    "summoning" => (DeskItem::Summoning, Some("summoning_interact")),
-                    "doorway" => (DeskItem::Doorway, Some("doorway_interact")),
-                    "journal" => (DeskItem::Journal, Some("journal_interact")),
-                    "potion" => (DeskItem::Potion, None),
-                    "candle0" => (DeskItem::Candle(0), None),
-                    "candle1" => (DeskItem::Candle(1), None),
-                    "candle2" => (DeskItem::Candle(2), None),
-                    "candle3" => (DeskItem::Candle(3), None),
-                    "candle4" => (DeskItem::Candle(4), None),
-                    "doorway_candle0" => (DeskItem::DoorwayCandle(0), None),
-                    "doorway_candle1" => (DeskItem::DoorwayCandle(1), None),
-                    _ => continue,
-                };
-                let interactable = match item {
-                    DeskItem::Candle(idx) => Interactable::Candle(idx),
-                    DeskItem::DoorwayCandle(idx) => Interactable::DoorwayCandle(idx),
-                    DeskItem::Potion => Interactable::Potion,
-                    DeskItem::Journal => Interactable::Journal,
-                    DeskItem::Doorway => Interactable::Doorway,
-                    // item => Interactable::Contextable(Contextable::DeskItem(item)),
-                    _ => Interactable::Backdrop,
+            let layout = game_assets
+                .desk_layout(&desk.layout)
+                .and_then(|handle| layouts.get(&handle));
+            if layout.is_none() {
+                println!("Missing desk layout {}", desk.layout);
+                continue;
+            }
+            let layout = layout.unwrap();
+            for error in validate_desk_layout(layout, &spine) {
+                println!("Bad desk layout {}: {}", desk.layout, error);
+            }
+
+            for entry in layout.items.iter() {
+                println!("Adding context item: {:?}", entry.item);
+                let entity = match &entry.at {
+                    Placement::Bone(bone_name) => event.bones.get(bone_name).copied(),
+                    Placement::MovedBone(bone_name, position) => {
+                        if let Some(mut bone) = spine.skeleton.find_bone_mut(bone_name) {
+                            bone.set_position(*position);
+                        }
+                        event.bones.get(bone_name).copied()
+                    }
+                    Placement::At(position) => {
+                        let entity = commands
+                            .spawn(TransformBundle::from_transform(
+                                Transform::from_translation(position.extend(0.0)),
+                            ))
+                            .id();
+                        commands.entity(event.entity).add_child(entity);
+                        Some(entity)
+                    }
                 };
-                commands
-                    .entity(*bone)
-                    .insert((item, interactable, DeskItemState::default()));
-                if let Some(bounding_slot) = bounding_slot {
-                    let collider = get_polygon_for_bounding_box(&spine, bounding_slot);
-                    commands.entity(*bone).insert((
+                if entity.is_none() {
+                    continue;
+                }
+                let entity = entity.unwrap();
+                let item = entry.item;
+                commands.entity(entity).insert((
+                    item,
+                    item.interactable(),
+                    DeskItemState::default(),
+                    InRoom(event.entity),
+                ));
+                if entry.demon_usable() {
+                    commands.entity(entity).insert(DemonUsable);
+                }
+                if let Some(wick) = &entry.wick {
+                    commands.entity(entity).insert((
+                        Candle { item, wax: 1.0 },
+                        Wick {
+                            bone: wick.clone(),
+                            lit: false,
+                            lit_order: None,
+                        },
+                    ));
+                }
+                if let Some(collider) = entry
+                    .shape
+                    .as_ref()
+                    .and_then(|shape| get_collider_for_shape(&spine, shape))
+                {
+                    commands.entity(entity).insert((
                         Sensor,
                         ActiveEvents::COLLISION_EVENTS,
                         collider,
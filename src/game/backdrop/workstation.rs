@@ -0,0 +1,114 @@
+use bevy::utils::{HashMap, HashSet};
+
+use crate::{
+    game::{DemonDna, DeskItemState, Inventory, ProductKind},
+    prelude::*,
+};
+
+use super::InRoom;
+
+pub struct WorkCompletion {
+    pub user: Option<Entity>,
+    pub dna: DemonDna,
+    pub room: Entity, // The desk the job was done at.
+}
+
+pub type CompletionHook = fn(&mut Commands, &mut Spine, &WorkCompletion);
+
+#[derive(Clone)]
+pub struct Workstation {
+    pub track: usize,
+    pub work_animation: &'static str,
+    // None when the completion hook decides what, if anything, the job makes.
+    pub product: Option<ProductKind>,
+    pub on_complete: Option<CompletionHook>,
+}
+
+// Sent when a demon finishes a job and its product goes on the shelf.
+#[derive(Event, Debug, Clone)]
+pub struct JobCompleted {
+    pub item: DeskItem,
+    pub product: ProductKind,
+    pub maker: DemonDna,
+}
+
+#[derive(Resource, Default)]
+pub struct Workstations(pub HashMap<DeskItem, Workstation>);
+
+pub trait WorkstationAppExt {
+    fn register_workstation(&mut self, item: DeskItem, workstation: Workstation) -> &mut Self;
+}
+
+impl WorkstationAppExt for App {
+    fn register_workstation(&mut self, item: DeskItem, workstation: Workstation) -> &mut Self {
+        self.init_resource::<Workstations>();
+        self.world_mut()
+            .resource_mut::<Workstations>()
+            .0
+            .insert(item, workstation);
+        self
+    }
+}
+
+pub fn run_workstations(
+    mut commands: Commands,
+    time: Res<Time>,
+    workstations: Res<Workstations>,
+    mut inventory: ResMut<Inventory>,
+    mut job_events: EventWriter<JobCompleted>,
+    mut desks: Query<&mut Spine, With<Desk>>,
+    mut items: Query<(&DeskItem, &mut DeskItemState, &InRoom)>,
+) {
+    // Tools of the same kind in a room share a track, so it plays while any of them is busy.
+    let busy: HashSet<(Entity, DeskItem)> = items
+        .iter()
+        .filter(|(_, state, _)| state.user.is_some())
+        .map(|(item, _, room)| (room.0, *item))
+        .collect();
+    for (item, mut state, room) in items.iter_mut() {
+        let desk = desks.get_mut(room.0);
+        if desk.is_err() {
+            continue;
+        }
+        let mut desk = desk.unwrap();
+        if let Some(workstation) = workstations.0.get(item) {
+            if state.user.is_some() {
+                if get_current_animation(&desk, workstation.track).is_none() {
+                    desk.animation_state.set_animation_by_name(
+                        workstation.track,
+                        workstation.work_animation,
+                        true,
+                    );
+                }
+            } else if !busy.contains(&(room.0, *item))
+                && get_current_animation(&desk, workstation.track).as_deref()
+                    == Some(workstation.work_animation)
+            {
+                // Only stop our own animation, the track may be shared with others like summoning.
+                desk.animation_state
+                    .set_empty_animation(workstation.track, 0.);
+            }
+            if let Some(dna) = state.just_completed {
+                let completion = WorkCompletion {
+                    user: state.user,
+                    dna,
+                    room: room.0,
+                };
+                state.just_completed = None;
+                state.progress = 0.0;
+                state.user = None;
+                if let Some(on_complete) = workstation.on_complete {
+                    on_complete(&mut commands, &mut desk, &completion);
+                }
+                if let Some(product) = workstation.product {
+                    inventory.add(product, dna, time.elapsed_seconds());
+                    job_events.send(JobCompleted {
+                        item: *item,
+                        product,
+                        maker: dna,
+                    });
+                }
+            }
+        }
+    }
+}
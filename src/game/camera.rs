@@ -1,10 +1,23 @@
-use bevy::input::mouse::MouseWheel;
+use bevy::{
+    input::mouse::MouseWheel,
+    render::camera::ScalingMode,
+    window::{PrimaryWindow, WindowMode},
+};
 
-use crate::prelude::*;
+use crate::{game::CurrentRoom, prelude::*};
+
+// The view the art was laid out for. Bigger or oddly shaped windows see more, never less.
+pub const DESIGN_WIDTH: f32 = 948.0;
+pub const DESIGN_HEIGHT: f32 = 533.0;
 
 pub fn spawn_camera(mut commands: Commands) {
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: DESIGN_WIDTH,
+        min_height: DESIGN_HEIGHT,
+    };
     commands
-        .spawn(Camera2dBundle::default())
+        .spawn((camera, CameraRig::default()))
         .with_children(|parent| {
             parent.spawn((TransformBundle::default(), MainChatAttach));
         });
@@ -16,45 +29,229 @@ pub fn despawn_camera(mut commands: Commands, query: Query<Entity, With<Camera2d
     }
 }
 
+const MIN_SCALE: f32 = 0.1;
+const MAX_SCALE: f32 = 1.0;
+const FRAME_SCALE: f32 = 0.35;
+const EASING: f32 = 8.0;
+const DOUBLE_CLICK_TIME: f32 = 0.35;
+// How far inside the view's edge a focused item is kept.
+const FOCUS_MARGIN: f32 = 60.0;
+
+// Where the camera is headed. The transform eases towards it every frame.
+#[derive(Component)]
+pub struct CameraRig {
+    pub target: Vec2,
+    pub target_scale: f32,
+    pub follow: Option<Entity>,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            target: Vec2::ZERO,
+            target_scale: MAX_SCALE,
+            follow: None,
+        }
+    }
+}
+
 pub fn zoom_and_move_camera(
     mut mouse_wheel_events: EventReader<MouseWheel>,
-    mut query: Query<(&mut Transform, &Camera)>,
-    kb_input: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
+    mut query: Query<&mut CameraRig>,
+    actions: Res<ActionState>,
+    interact_state: Res<InteractState>,
+    ui: Query<&Interaction>,
+    time: Res<Time<Real>>,
 ) {
     let mut mouse_zoom = 0.0;
     for event in mouse_wheel_events.read() {
         mouse_zoom = event.y;
     }
-    for (mut transform, camera) in query.iter_mut() {
+    if ui
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        // The wheel is scrolling something on screen instead.
+        mouse_zoom = 0.0;
+    }
+    for mut rig in query.iter_mut() {
         let mut direction = Vec3::ZERO;
-        if kb_input.pressed(KeyCode::KeyW) || kb_input.pressed(KeyCode::ArrowUp) {
+        if actions.pressed(InputAction::PanUp) {
             direction.y += 1.0;
         }
-        if kb_input.pressed(KeyCode::KeyS) || kb_input.pressed(KeyCode::ArrowDown) {
+        if actions.pressed(InputAction::PanDown) {
             direction.y -= 1.0;
         }
-        if kb_input.pressed(KeyCode::KeyA) || kb_input.pressed(KeyCode::ArrowLeft) {
+        if actions.pressed(InputAction::PanLeft) {
             direction.x -= 1.0;
         }
-        if kb_input.pressed(KeyCode::KeyD) || kb_input.pressed(KeyCode::ArrowRight) {
+        if actions.pressed(InputAction::PanRight) {
             direction.x += 1.0;
         }
-        if kb_input.pressed(KeyCode::Equal) {
+        if actions.pressed(InputAction::ZoomIn) {
             direction.z += 1.0;
         }
-        if kb_input.pressed(KeyCode::Minus) {
+        if actions.pressed(InputAction::ZoomOut) {
             direction.z -= 1.0;
         }
         direction.z += mouse_zoom * 10.;
         if direction.z != 0.0 {
-            transform.scale -= Vec3::splat(direction.z * time.delta_seconds());
-            transform.scale = transform.scale.clamp(Vec3::splat(0.1), Vec3::splat(1.0));
+            let old_scale = rig.target_scale;
+            rig.target_scale =
+                (old_scale - direction.z * time.delta_seconds()).clamp(MIN_SCALE, MAX_SCALE);
+            // Keep the point under the cursor where it is.
+            let cursor = interact_state.mouse_location;
+            rig.target = cursor + (rig.target - cursor) * (rig.target_scale / old_scale);
         }
         if direction.truncate() != Vec2::ZERO {
-            let speed = 500.0 * transform.scale.x.sqrt();
-            transform.translation +=
-                (direction.truncate().normalize() * time.delta_seconds() * speed).extend(0.0);
+            let speed = 500.0 * rig.target_scale.sqrt();
+            rig.target += direction.truncate().normalize() * time.delta_seconds() * speed;
+            rig.follow = None;
+        }
+    }
+}
+
+pub fn toggle_camera_follow(
+    actions: Res<ActionState>,
+    interact_state: Res<InteractState>,
+    focus: Res<FocusState>,
+    demons: Query<(), With<Demon>>,
+    mut query: Query<&mut CameraRig>,
+) {
+    if !actions.just_pressed(InputAction::Follow) {
+        return;
+    }
+    let demon = [focus.focused, interact_state.hovered]
+        .into_iter()
+        .flatten()
+        .find(|entity| demons.contains(*entity));
+    for mut rig in query.iter_mut() {
+        // Pressing again, or with no demon picked, stops following.
+        rig.follow = if demon.is_some() && rig.follow != demon {
+            demon
+        } else {
+            None
+        };
+    }
+}
+
+// Pans just enough to bring whatever the D-pad focused on screen, so a pad can reach every item.
+pub fn keep_focus_in_view(
+    focus: Res<FocusState>,
+    transforms: Query<&GlobalTransform>,
+    mut query: Query<(&mut CameraRig, &OrthographicProjection)>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    let focused = focus
+        .focused
+        .and_then(|focused| transforms.get(focused).ok());
+    if focused.is_none() {
+        return;
+    }
+    let position = focused.unwrap().translation().truncate();
+    for (mut rig, projection) in query.iter_mut() {
+        let half_view = projection.area.size() * 0.5 * rig.target_scale;
+        let reach = (half_view - Vec2::splat(FOCUS_MARGIN)).max(Vec2::ZERO);
+        let target = rig.target.clamp(position - reach, position + reach);
+        if target != rig.target {
+            rig.target = target;
+            rig.follow = None;
         }
     }
 }
+
+pub fn frame_on_double_click(
+    mut interact_events: EventReader<InteractEvent>,
+    mut last_press: Local<Option<(Entity, f32)>>,
+    time: Res<Time<Real>>,
+    transforms: Query<&GlobalTransform>,
+    mut query: Query<&mut CameraRig>,
+) {
+    for event in interact_events.read() {
+        if event.interact_type != InteractType::Press
+            || matches!(
+                event.interactable,
+                Interactable::Backdrop | Interactable::ContextItem | Interactable::ShelfSlot(_)
+            )
+        {
+            continue;
+        }
+        let now = time.elapsed_seconds();
+        let double = last_press
+            .map(|(entity, at)| entity == event.entity && now - at < DOUBLE_CLICK_TIME)
+            .unwrap_or(false);
+        if double {
+            *last_press = None;
+            if let Ok(transform) = transforms.get(event.entity) {
+                for mut rig in query.iter_mut() {
+                    rig.target = transform.translation().truncate();
+                    rig.target_scale = FRAME_SCALE;
+                    rig.follow = None;
+                }
+            }
+        } else {
+            *last_press = Some((event.entity, now));
+        }
+    }
+}
+
+pub fn ease_camera(
+    time: Res<Time<Real>>,
+    current: Res<CurrentRoom>,
+    desk: Query<(&Desk, &GlobalTransform)>,
+    followed: Query<&GlobalTransform, Without<CameraRig>>,
+    mut query: Query<(&mut Transform, &mut CameraRig, &OrthographicProjection)>,
+) {
+    for (mut transform, mut rig, projection) in query.iter_mut() {
+        if let Some(entity) = rig.follow {
+            match followed.get(entity) {
+                Ok(followed) => rig.target = followed.translation().truncate(),
+                Err(_) => rig.follow = None,
+            }
+        }
+        // Keep the view inside the room's desk, or centred on it when zoomed out past its edges.
+        if let Some((desk, desk_transform)) = current.0.and_then(|room| desk.get(room).ok()) {
+            let center = desk_transform.translation().truncate();
+            let half_desk = Vec2::new(desk.width, desk.height);
+            let half_view = projection.area.size() * 0.5 * rig.target_scale;
+            let slack = (half_desk - half_view).max(Vec2::ZERO);
+            rig.target = rig.target.clamp(center - slack, center + slack);
+        }
+        let t = 1.0 - (-EASING * time.delta_seconds()).exp();
+        let translation = transform.translation.truncate().lerp(rig.target, t);
+        transform.translation = translation.extend(transform.translation.z);
+        let scale = transform.scale.x + (rig.target_scale - transform.scale.x) * t;
+        transform.scale = Vec3::splat(scale);
+    }
+}
+
+// UI is laid out in pixels, so it grows and shrinks with the window like the desk does.
+pub fn scale_ui(
+    mut ui_scale: ResMut<UiScale>,
+    window: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+) {
+    if let Some(window) = window.iter().next() {
+        let scale = (window.width() / DESIGN_WIDTH).min(window.height() / DESIGN_HEIGHT);
+        if scale > 0.0 && (ui_scale.0 - scale).abs() > 0.01 {
+            ui_scale.0 = scale;
+        }
+    }
+}
+
+pub fn toggle_fullscreen(
+    actions: Res<ActionState>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !actions.just_pressed(InputAction::ToggleFullscreen) {
+        return;
+    }
+    for mut window in window.iter_mut() {
+        window.mode = if window.mode == WindowMode::Windowed {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+    }
+}
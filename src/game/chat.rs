@@ -1,8 +1,11 @@
+use std::collections::VecDeque;
+
 use bevy::text::Text2dBounds;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::prelude::*;
 
-use super::DEMON_MAIN_TRACK;
+use super::{get_name, log_chat, markup_sections, play_babble, strip_markup, DEMON_MAIN_TRACK};
 
 #[derive(Component)]
 pub struct ChatBox {
@@ -15,6 +18,30 @@ pub struct ChatBox {
 #[derive(Component, Clone)]
 pub struct AttachedChatBox(pub Entity);
 
+#[derive(Resource)]
+pub struct ChatSettings {
+    pub reveal_speed: f32, // Graphemes per second.
+    pub auto_dismiss: bool,
+}
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        Self {
+            reveal_speed: 40.0,
+            auto_dismiss: true,
+        }
+    }
+}
+
+// Reveals a chat box's text a grapheme at a time once the box has arrived.
+#[derive(Component, Default)]
+pub struct Typewriter {
+    started: bool,
+    shown: usize,
+    elapsed: f32,
+    finished_for: f32,
+}
+
 #[derive(Component)]
 pub struct MainChatAttach;
 
@@ -54,6 +81,15 @@ fn despawn_old_chat_box(id: Entity, world: &mut World) {
     }
 }
 
+// Forgets a dismissed box, unless the attachment has moved on to a newer one already.
+fn forget_chat_box(chat: Entity) -> impl FnOnce(Entity, &mut World) {
+    move |id: Entity, world: &mut World| {
+        if world.get::<AttachedChatBox>(id).map(|attached| attached.0) == Some(chat) {
+            world.entity_mut(id).remove::<AttachedChatBox>();
+        }
+    }
+}
+
 pub fn spawn_chat_box(
     commands: &mut Commands,
     skeleton: Handle<SkeletonData>,
@@ -70,6 +106,7 @@ pub fn spawn_chat_box(
                 ..Default::default()
             },
             chat_box,
+            Typewriter::default(),
         ))
         .set_parent(parent)
         .id();
@@ -84,6 +121,7 @@ pub fn manage_chat_boxes(
     mut ready_events: EventReader<SpineReadyEvent>,
     mut spine_events: EventReader<SpineEvent>,
     mut spine: Query<&mut Spine>,
+    mut typewriters: Query<&mut Typewriter>,
 ) {
     for (entity, chat_box) in chat_box.iter() {
         if let Ok(_global) = globals.get(chat_box.attachment) {
@@ -103,6 +141,9 @@ pub fn manage_chat_boxes(
                     {
                         text_entity.insert(Visibility::Visible);
                     }
+                    if let Ok(mut typewriter) = typewriters.get_mut(*entity) {
+                        typewriter.started = true;
+                    }
                 }
             }
         }
@@ -126,16 +167,9 @@ pub fn manage_chat_boxes(
             if let Some(mut text_attach) = commands.get_entity(*text_attach) {
                 text_attach.with_children(|parent| {
                     let text_entity = parent.spawn(Text2dBundle {
-                        text: Text::from_section(
-                            chat_box.text.clone(),
-                            TextStyle {
-                                color: Color::BLACK,
-                                font_size: 12.,
-                                ..Default::default()
-                            },
-                        ),
+                        text: Text::from_section("", chat_text_style()),
                         text_2d_bounds: Text2dBounds {
-                            size: Vec2::new(175.0, 90.0),
+                            size: CHAT_TEXT_SIZE,
                         },
                         transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
                         visibility: Visibility::Hidden,
@@ -156,20 +190,138 @@ pub type MainChat<'w, 's> = (
     Res<'w, Skeletons>,
 );
 
-pub fn spawn_main_chat_box(
-    commands: &mut Commands,
-    main_chat: &Query<(Entity, &MainChatAttach)>,
-    skeletons: &Skeletons,
-    style: &'static str,
-    text: String,
+// Main-chat messages wait their turn, so one can't wipe out another before it's been read.
+#[derive(Resource, Default)]
+pub struct MainChatQueue {
+    waiting: VecDeque<(&'static str, String)>,
+    showing_until: f32,
+}
+
+const CHAT_TEXT_SIZE: Vec2 = Vec2::new(175.0, 90.0);
+
+// Whether a point in the world lands on a chat box's text.
+fn on_chat_text(text: &GlobalTransform, point: Vec2) -> bool {
+    let transform = text.compute_transform();
+    let half = CHAT_TEXT_SIZE * 0.5 * transform.scale.truncate().abs();
+    let offset = point - transform.translation.truncate();
+    offset.x.abs() <= half.x && offset.y.abs() <= half.y
+}
+
+fn chat_text_style() -> TextStyle {
+    TextStyle {
+        color: Color::BLACK,
+        font_size: 12.,
+        ..Default::default()
+    }
+}
+
+fn visible_length(text: &str) -> usize {
+    strip_markup(text).graphemes(true).count()
+}
+
+pub fn reading_time(text: &str) -> f32 {
+    2.0 + visible_length(text) as f32 * 0.05
+}
+
+pub fn reveal_time(text: &str, settings: &ChatSettings) -> f32 {
+    visible_length(text) as f32 / settings.reveal_speed.max(1.0)
+}
+
+pub fn type_chat_text(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<ChatSettings>,
+    actions: Res<ActionState>,
+    interact_state: Res<InteractState>,
+    mut chat_boxes: Query<(Entity, &ChatBox, &mut Typewriter)>,
+    mut texts: Query<(&mut Text, &GlobalTransform)>,
+) {
+    let click = if actions.just_pressed(InputAction::Interact) {
+        Some(interact_state.mouse_location)
+    } else {
+        None
+    };
+    for (entity, chat_box, mut typewriter) in chat_boxes.iter_mut() {
+        if !typewriter.started {
+            continue;
+        }
+        let total = visible_length(&chat_box.text);
+        if typewriter.shown < total {
+            // Clicking on a box skips it straight to the full text, other boxes keep typing.
+            let skip = click
+                .zip(chat_box.text_entity.and_then(|text| texts.get(text).ok()))
+                .map(|(click, (_, global))| on_chat_text(global, click))
+                .unwrap_or(false);
+            typewriter.elapsed += time.delta_seconds();
+            let shown = if skip {
+                total
+            } else {
+                ((typewriter.elapsed * settings.reveal_speed) as usize).min(total)
+            };
+            if shown != typewriter.shown {
+                typewriter.shown = shown;
+                if let Some((mut text, _)) = chat_box
+                    .text_entity
+                    .and_then(|text_entity| texts.get_mut(text_entity).ok())
+                {
+                    text.sections = markup_sections(&chat_box.text, &chat_text_style(), shown);
+                }
+            }
+        } else if settings.auto_dismiss {
+            typewriter.finished_for += time.delta_seconds();
+            if typewriter.finished_for > reading_time(&chat_box.text) {
+                commands.entity(entity).despawn_recursive();
+                if let Some(mut attachment) = commands.get_entity(chat_box.attachment) {
+                    attachment.add(forget_chat_box(entity));
+                }
+            }
+        }
+    }
+}
+
+pub fn spawn_main_chat_box(commands: &mut Commands, style: &'static str, text: String) {
+    commands.add(move |world: &mut World| {
+        world
+            .resource_mut::<MainChatQueue>()
+            .waiting
+            .push_back((style, text));
+    });
+}
+
+pub fn show_main_chat(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut queue: ResMut<MainChatQueue>,
+    main_chat: Query<(Entity, Option<&AttachedChatBox>), With<MainChatAttach>>,
+    chat_boxes: Query<(), With<ChatBox>>,
+    skeletons: Res<Skeletons>,
+    settings: Res<ChatSettings>,
 ) {
+    if queue.waiting.is_empty() {
+        return;
+    }
+    let main_chat = main_chat.iter().next();
+    if main_chat.is_none() {
+        return;
+    }
+    let (attachment, attached) = main_chat.unwrap();
+    let showing = attached
+        .map(|attached| chat_boxes.contains(attached.0))
+        .unwrap_or(false);
+    if showing && time.elapsed_seconds() < queue.showing_until {
+        return;
+    }
+    let (style, text) = queue.waiting.pop_front().unwrap();
+    queue.showing_until =
+        time.elapsed_seconds() + reveal_time(&text, &settings) + reading_time(&text);
+    log_chat(&mut commands, "Narrator".to_string(), text.clone());
     let chat_box = ChatBox {
         style,
-        attachment: main_chat.iter().next().unwrap().0,
+        attachment,
         text_entity: None,
         text,
     };
-    spawn_chat_box(commands, skeletons.chat.clone(), chat_box);
+    spawn_chat_box(&mut commands, skeletons.chat.clone(), chat_box);
 }
 
 pub fn spawn_demon_chat_box(
@@ -177,11 +329,13 @@ pub fn spawn_demon_chat_box(
     demon: &mut Demon,
     skeletons: &Skeletons,
     text: String,
-    chat_state: &'static str,
+    chat_state: &str,
 ) {
+    log_chat(commands, get_name(&demon.dna), text.clone());
+    play_babble(commands, demon.dna, demon.nonce, &text);
     let chat_box = ChatBox::talk(demon.chat_attach.unwrap(), text);
     spawn_chat_box(commands, skeletons.chat.clone(), chat_box);
-    demon.chatting = Some(chat_state);
+    demon.chatting = Some(chat_state.to_string());
 }
 
 pub fn despawn_demon_chat_boxes(
@@ -197,9 +351,7 @@ pub fn despawn_demon_chat_boxes(
                 commands
                     .get_entity(*chat)
                     .map(|chat| chat.despawn_recursive());
-            } else if demon.chatting.map(|str| str.to_string())
-                != get_current_animation(spine, DEMON_MAIN_TRACK)
-            {
+            } else if demon.chatting != get_current_animation(spine, DEMON_MAIN_TRACK) {
                 commands
                     .get_entity(*chat)
                     .map(|chat| chat.despawn_recursive());
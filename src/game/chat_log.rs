@@ -0,0 +1,170 @@
+use bevy::input::mouse::MouseWheel;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+use super::markup_sections;
+
+const MAX_ENTRIES: usize = 500;
+const LOG_HEIGHT: f32 = 220.0;
+const SCROLL_SPEED: f32 = 20.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatLogEntry {
+    pub session: u32,
+    pub at: f32,
+    pub speaker: String,
+    pub text: String,
+}
+
+impl ChatLogEntry {
+    pub fn timestamp(&self) -> String {
+        let seconds = self.at as u32;
+        format!("#{} {:02}:{:02}", self.session, seconds / 60, seconds % 60)
+    }
+}
+
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatLog {
+    pub session: u32,
+    pub entries: Vec<ChatLogEntry>,
+}
+
+impl ChatLog {
+    pub fn record(&mut self, speaker: String, text: String, at: f32) {
+        self.entries.push(ChatLogEntry {
+            session: self.session,
+            at,
+            speaker,
+            text,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            let extra = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..extra);
+        }
+    }
+}
+
+pub fn log_chat(commands: &mut Commands, speaker: String, text: String) {
+    commands.add(move |world: &mut World| {
+        let at = world.resource::<Time>().elapsed_seconds();
+        world.resource_mut::<ChatLog>().record(speaker, text, at);
+    });
+}
+
+#[derive(Resource, Default)]
+pub struct ChatLogState {
+    pub open: bool,
+}
+
+#[derive(Component)]
+pub struct ChatLogPanel;
+
+#[derive(Component, Default)]
+pub struct ChatLogList {
+    position: f32,
+}
+
+pub fn toggle_chat_log(actions: Res<ActionState>, mut state: ResMut<ChatLogState>) {
+    if actions.just_pressed(InputAction::ToggleLog) {
+        state.open = !state.open;
+    }
+}
+
+pub fn draw_chat_log(
+    mut commands: Commands,
+    state: Res<ChatLogState>,
+    log: Res<ChatLog>,
+    panels: Query<Entity, With<ChatLogPanel>>,
+) {
+    if !state.is_changed() && !log.is_changed() {
+        return;
+    }
+    for entity in panels.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !state.open {
+        return;
+    }
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        font_size: 13.,
+        ..Default::default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(8.0),
+                    left: Val::Px(8.0),
+                    width: Val::Px(360.0),
+                    height: Val::Px(LOG_HEIGHT),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    overflow: Overflow::clip_y(),
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0.05, 0.05, 0.05, 0.85).into(),
+                ..Default::default()
+            },
+            Interaction::default(),
+            ChatLogPanel,
+        ))
+        .with_children(|panel| {
+            panel
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(4.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    ChatLogList::default(),
+                ))
+                .with_children(|list| {
+                    if log.entries.is_empty() {
+                        list.spawn(TextBundle::from_section(
+                            "Nothing has been said yet.",
+                            text_style.clone(),
+                        ));
+                    }
+                    // Newest first, so it opens on the latest message.
+                    for entry in log.entries.iter().rev() {
+                        let mut sections = vec![TextSection::new(
+                            format!("{} {}: ", entry.timestamp(), entry.speaker),
+                            TextStyle {
+                                color: Color::srgb(0.9, 0.7, 0.4),
+                                ..text_style.clone()
+                            },
+                        )];
+                        sections.extend(markup_sections(&entry.text, &text_style, usize::MAX));
+                        list.spawn(TextBundle::from_sections(sections));
+                    }
+                });
+        });
+}
+
+pub fn scroll_chat_log(
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    panels: Query<(&Interaction, &Node, &Children), With<ChatLogPanel>>,
+    mut lists: Query<(&mut ChatLogList, &mut Style, &Node)>,
+) {
+    let scroll: f32 = mouse_wheel_events.read().map(|event| event.y).sum();
+    if scroll == 0.0 {
+        return;
+    }
+    for (interaction, panel_node, children) in panels.iter() {
+        if *interaction == Interaction::None {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok((mut list, mut style, list_node)) = lists.get_mut(*child) {
+                let max_scroll = (list_node.size().y - panel_node.size().y).max(0.0);
+                list.position = (list.position + scroll * SCROLL_SPEED).clamp(-max_scroll, 0.0);
+                style.top = Val::Px(list.position);
+            }
+        }
+    }
+}
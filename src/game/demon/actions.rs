@@ -2,35 +2,72 @@ use behavior_bark::unpowered::UnpoweredFunctionState;
 
 use crate::{
     game::{
-        get_berate, get_complain, get_interrupted, spawn_demon_chat_box, spawn_main_chat_box,
-        DeskItemState, MainChat, MainChatAttach,
+        spawn_demon_chat_box, spawn_main_chat_box, DemonUsable, DeskItemState, DeskLight, InRoom,
+        MainChat, MainChatAttach, Travelled, Upgrades,
     },
     prelude::*,
 };
 
-use super::{get_introduction, DemonBrain, DemonModel, Distraction};
+use super::{
+    BrainTrace, DemonArchetype, DemonBrain, DemonBrainDef, DemonGrammar, DemonModel, Distraction,
+    DistractionEnd, Distractions,
+};
 
 pub const DEMON_MAIN_TRACK: usize = 0;
 
+// The closest usable item of a kind in the room, since a bought tool can sit beside the desk's own.
+fn nearest_of_kind(
+    desk_items: &Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &DeskItem,
+            &mut DeskItemState,
+            &InRoom,
+        ),
+        (With<DemonUsable>, Without<Demon>),
+    >,
+    kind: DeskItem,
+    room: &InRoom,
+    from: Vec3,
+) -> Option<Entity> {
+    desk_items
+        .iter()
+        .filter(|(_, _, item, _, in_room)| **item == kind && *in_room == room)
+        .min_by(|(_, a, _, _, _), (_, b, _, _, _)| {
+            a.translation()
+                .distance(from)
+                .total_cmp(&b.translation().distance(from))
+        })
+        .map(|(entity, _, _, _, _)| entity)
+}
+
 pub fn activate_demons(
     time: Res<Time>,
-    mut desk: Query<&mut Spine, With<Desk>>,
+    rooms: Query<&DeskLight>,
     mut query: Query<
-        (Entity, &Transform, &mut Demon, &mut Spine),
+        (Entity, &Transform, &mut Demon, &mut Spine, &InRoom),
         (Without<DeskItem>, Without<Desk>),
     >,
-    mut desk_items: Query<(&Transform, &DeskItem, &mut DeskItemState), Without<Demon>>,
+    mut desk_items: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &DeskItem,
+            &mut DeskItemState,
+            &InRoom,
+        ),
+        (With<DemonUsable>, Without<Demon>),
+    >,
     mut velocities: Query<&mut Velocity>,
     mut commands: Commands,
     main_chat: Query<(Entity, &MainChatAttach)>,
     skeletons: Res<Skeletons>,
+    distractions: Res<Distractions>,
+    grammar: Res<DemonGrammar>,
+    upgrades: Res<Upgrades>,
 ) {
-    let desk = desk.iter_mut().next();
-    if desk.is_none() {
-        return;
-    }
-    let desk = desk.unwrap();
-    for (entity, transform, mut demon, mut spine) in query.iter_mut() {
+    for (entity, transform, mut demon, mut spine, room) in query.iter_mut() {
         if demon.chat_attach.is_none() {
             // Not initialized yet!
             continue;
@@ -49,7 +86,7 @@ pub fn activate_demons(
                         .animation_state
                         .set_animation_by_name(DEMON_MAIN_TRACK, "introduce", true)
                         .expect("Failed to set animation");
-                    let introduction = get_introduction(&demon.dna);
+                    let introduction = grammar.introduce(&demon.dna, demon.nonce);
                     spawn_demon_chat_box(
                         &mut commands,
                         &mut demon,
@@ -77,9 +114,10 @@ pub fn activate_demons(
                         .animation_state
                         .add_empty_animation(DEMON_MAIN_TRACK, 0., 0.);
                 }
-                let desk_item = desk_items.iter().find(|(_, item, _)| *item == target);
-                if let Some((target_transform, _, _)) = desk_item {
-                    let direction = target_transform.translation - transform.translation;
+                let desk_item = nearest_of_kind(&desk_items, *target, room, transform.translation)
+                    .and_then(|item| desk_items.get(item).ok());
+                if let Some((_, target_transform, _, _, _)) = desk_item {
+                    let direction = target_transform.translation() - transform.translation;
                     let direction = direction.truncate();
                     let mut velocity = velocities.get_mut(entity).unwrap();
                     velocity.linvel = direction.normalize() * 100.0;
@@ -90,21 +128,37 @@ pub fn activate_demons(
                         let scale = spine.skeleton.scale_x().abs();
                         spine.skeleton.set_scale_x(scale);
                     }
+                } else if demon.assigned_tool == Some(*target) {
+                    // Sent to a tool this room doesn't have, so give up on it.
+                    println!("Assigned {:?} is out of reach", target);
+                    demon.assigned_tool = None;
+                    demon.action = DemonController::Idle;
                 }
             }
             DemonController::UseTool => {
-                if let Some((_, item, mut state)) = desk_items
-                    .iter_mut()
-                    .find(|(_, item, _)| Some(**item) == demon.in_area_for_tool)
+                // Stick with the tool already in use, else take the closest one of its kind.
+                let nearest = desk_items
+                    .iter()
+                    .find(|(_, _, _, state, _)| state.user == Some(entity))
+                    .map(|(item, _, _, _, _)| item)
+                    .or_else(|| {
+                        demon.in_area_for_tool.and_then(|kind| {
+                            nearest_of_kind(&desk_items, kind, room, transform.translation)
+                        })
+                    });
+                if let Some((_, _, _, mut state, _)) =
+                    nearest.and_then(|item| desk_items.get_mut(item).ok())
                 {
                     if state.user == Some(entity) {
                         // Already using tool
-                        state.progress += time.delta_seconds();
+                        let light = rooms.get(room.0).copied().unwrap_or_default();
+                        state.progress +=
+                            time.delta_seconds() * light.work_speed() * upgrades.work_speed();
                         continue;
                     } else if state.user.is_some() {
                         // Someone else is using the tool
                         demon.action = DemonController::Distracted(
-                            Distraction::Berate,
+                            Distraction::berate(),
                             Box::new(demon.action.clone()),
                         );
                         continue;
@@ -116,118 +170,72 @@ pub fn activate_demons(
                 let mut velocity = velocities.get_mut(entity).unwrap();
                 velocity.linvel = Vec2::ZERO;
             }
-            DemonController::Distracted(something, _next) => {
+            DemonController::Distracted(something, next) => {
                 println!("Demon distracted: {:?}", something);
-                match something {
-                    Distraction::Sleep => {
-                        if spine
-                            .animation_state
-                            .get_current(DEMON_MAIN_TRACK)
-                            .map(|current| current.animation().name().contains("sleep"))
-                            .unwrap_or(false)
-                        {
-                            // Already sleeping
-                        } else {
-                            spine
-                                .animation_state
-                                .set_animation_by_name(DEMON_MAIN_TRACK, "sleep", false)
-                                .expect("Failed to set animation");
-                            spine
-                                .animation_state
-                                .add_animation_by_name(DEMON_MAIN_TRACK, "sleep_loop", true, 0.)
-                                .expect("Cannot add sleep loop");
-                            let mut velocity = velocities.get_mut(entity).unwrap();
-                            velocity.linvel = Vec2::ZERO;
-                        }
+                let (something, next) = (something.clone(), next.clone());
+                let def = distractions.get(&something);
+                if def.is_none() {
+                    // Unknown or invalid distraction, so shrug it off.
+                    demon.action = *next;
+                    continue;
+                }
+                let def = def.unwrap();
+                if def.is_playing(get_current_animation(&spine, DEMON_MAIN_TRACK).as_ref()) {
+                    demon.distracted_for += time.delta_seconds();
+                    if def.ends == DistractionEnd::Timeout
+                        && demon.distracted_for >= def.duration.unwrap_or(0.)
+                    {
+                        demon.action = def.resume_to(&next);
                     }
-                    Distraction::Wander => {
-                        if spine
+                } else {
+                    demon.distracted_for = 0.;
+                    spine
+                        .animation_state
+                        .set_animation_by_name(DEMON_MAIN_TRACK, &def.animation, def.looping)
+                        .expect("Failed to set animation");
+                    if let Some(then_loop) = &def.then_loop {
+                        spine
                             .animation_state
-                            .get_current(DEMON_MAIN_TRACK)
-                            .map(|current| current.animation().name().contains("walk"))
-                            .unwrap_or(false)
-                        {
-                            // Already walking
-                        } else {
-                            spine
-                                .animation_state
-                                .set_animation_by_name(DEMON_MAIN_TRACK, "walk", false)
-                                .expect("Failed to set animation");
-                            spine
-                                .animation_state
-                                .add_empty_animation(DEMON_MAIN_TRACK, 0., 0.);
-                            let direction =
-                                Vec2::new(rand::random::<f32>() - 0.5, rand::random::<f32>() - 0.5);
-                            let mut velocity = velocities.get_mut(entity).unwrap();
-                            velocity.linvel = direction.normalize() * 50.0;
-                            if direction.x < 0.0 {
-                                let scale = spine.skeleton.scale_x().abs();
-                                spine.skeleton.set_scale_x(-scale);
-                            } else {
-                                let scale = spine.skeleton.scale_x().abs();
-                                spine.skeleton.set_scale_x(scale);
-                            }
-                        }
-                    }
-                    Distraction::Complain | Distraction::Berate | Distraction::Interrupted => {
-                        if spine
+                            .add_animation_by_name(DEMON_MAIN_TRACK, then_loop, true, 0.)
+                            .expect("Failed to add animation");
+                    } else if !def.looping {
+                        spine
                             .animation_state
-                            .get_current(DEMON_MAIN_TRACK)
-                            .map(|current| current.animation().name().contains("complain"))
-                            .unwrap_or(false)
-                        {
-                            // Already complaining
-                        } else {
-                            spine
-                                .animation_state
-                                .set_animation_by_name(DEMON_MAIN_TRACK, "complain", true)
-                                .expect("Failed to set animation");
-                            let mut velocity = velocities.get_mut(entity).unwrap();
-                            velocity.linvel = Vec2::ZERO;
-                            let text = if something == &Distraction::Berate {
-                                get_berate(&demon.dna)
-                            } else if something == &Distraction::Complain {
-                                get_complain(&demon.dna)
-                            } else {
-                                get_interrupted(&demon.dna)
-                            };
-                            spawn_demon_chat_box(
-                                &mut commands,
-                                &mut demon,
-                                &skeletons,
-                                text,
-                                "complain",
-                            );
-                        }
+                            .add_empty_animation(DEMON_MAIN_TRACK, 0., 0.);
                     }
-                    Distraction::Annoyed => {
-                        if spine
-                            .animation_state
-                            .get_current(DEMON_MAIN_TRACK)
-                            .map(|current| current.animation().name().contains("hit"))
-                            .unwrap_or(false)
-                        {
-                            // Already annoyed
+                    let mut velocity = velocities.get_mut(entity).unwrap();
+                    if def.speed > 0. {
+                        let direction =
+                            Vec2::new(rand::random::<f32>() - 0.5, rand::random::<f32>() - 0.5);
+                        velocity.linvel = direction.normalize() * def.speed;
+                        if direction.x < 0.0 {
+                            let scale = spine.skeleton.scale_x().abs();
+                            spine.skeleton.set_scale_x(-scale);
                         } else {
-                            spine
-                                .animation_state
-                                .set_animation_by_name(DEMON_MAIN_TRACK, "hit", false)
-                                .expect("Failed to set animation");
-                            spine
-                                .animation_state
-                                .add_empty_animation(DEMON_MAIN_TRACK, 0., 0.);
-                            let mut velocity = velocities.get_mut(entity).unwrap();
-                            velocity.linvel = Vec2::ZERO;
+                            let scale = spine.skeleton.scale_x().abs();
+                            spine.skeleton.set_scale_x(scale);
                         }
+                    } else {
+                        velocity.linvel = Vec2::ZERO;
+                    }
+                    if let Some(text) = def.get_chat(&demon.dna, demon.nonce, &grammar) {
+                        spawn_demon_chat_box(
+                            &mut commands,
+                            &mut demon,
+                            &skeletons,
+                            text,
+                            &def.animation,
+                        );
                     }
                 }
             }
             DemonController::FinishJob => {
-                for (_, _, mut state) in desk_items.iter_mut() {
+                for (_, _, _, mut state, _) in desk_items.iter_mut() {
                     if state.user == Some(entity) {
                         state.just_completed = Some(demon.dna.clone());
                     }
                 }
+                demon.assigned_tool = None;
             }
             DemonController::Idle => {}
         }
@@ -274,19 +282,24 @@ pub fn mark_demons_in_area(
 }
 
 pub fn mark_closest_item(
-    mut demons: Query<(&Transform, &mut Demon), Without<DeskItem>>,
-    desk_items: Query<(&Transform, &DeskItem), Without<Demon>>,
+    time: Res<Time>,
+    mut demons: Query<(&Transform, &mut Demon, &InRoom, Option<&Travelled>), Without<DeskItem>>,
+    desk_items: Query<(&GlobalTransform, &DeskItem, &InRoom), (With<DemonUsable>, Without<Demon>)>,
 ) {
-    for (demon_transform, mut demon) in demons.iter_mut() {
+    for (demon_transform, mut demon, room, travelled) in demons.iter_mut() {
+        // Don't head straight back out of the doorway just come through.
+        let just_arrived = travelled
+            .map(|travelled| travelled.just_arrived(time.elapsed_seconds()))
+            .unwrap_or(false);
         let mut closest_distance = f32::MAX;
         let mut closest_item = None;
-        for (item_transform, item) in desk_items.iter() {
-            if !item.demon_can_use() {
+        for (item_transform, item, in_room) in desk_items.iter() {
+            if in_room != room || (just_arrived && *item == DeskItem::Doorway) {
                 continue;
             }
             let distance = demon_transform
                 .translation
-                .distance(item_transform.translation);
+                .distance(item_transform.translation());
             if distance < closest_distance {
                 closest_distance = distance;
                 closest_item = Some(*item);
@@ -299,10 +312,23 @@ pub fn mark_closest_item(
 }
 
 pub fn control_demons(
-    mut query: Query<(Entity, &mut Demon, &mut DemonBrain)>,
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &mut Demon,
+        &mut DemonBrain,
+        &mut BrainTrace,
+        Option<&InRoom>,
+    )>,
     tools: Query<(&DeskItem, &DeskItemState)>,
+    distractions: Res<Distractions>,
+    rooms: Query<&DeskLight>,
 ) {
-    for (entity, mut demon, mut brains) in query.iter_mut() {
+    for (entity, mut demon, mut brains, mut trace, room) in query.iter_mut() {
+        let light = room
+            .and_then(|room| rooms.get(room.0).ok())
+            .copied()
+            .unwrap_or_default();
         let using_tool = tools.iter().find_map(|(item, state)| {
             if state.user == Some(entity) {
                 Some(state.progress)
@@ -315,14 +341,21 @@ pub fn control_demons(
             nonce: demon.nonce,
             in_range_of_tool: demon.in_area_for_tool.is_some(),
             nearest_tool: demon.nearest_tool,
+            assigned_tool: demon.assigned_tool,
+            potion: demon.potion.map(|potion| potion.effect),
+            light: light.0,
             using_tool,
+            distractions: distractions.clone(),
+            trace: Default::default(),
         };
         if matches!(demon.action, DemonController::Distracted(_, _))
             || matches!(demon.action, DemonController::Introduce)
         {
+            trace.observe_controller(&demon.action, time.elapsed_seconds());
             continue;
         }
         let result = brains.0.resume_with(&model, &mut demon.action);
+        trace.record_tick(&model, &demon.action, time.elapsed_seconds());
         match result {
             UnpoweredFunctionState::Complete => {
                 demon.nonce += 1;
@@ -335,9 +368,30 @@ pub fn control_demons(
     }
 }
 
+pub fn reload_demon_brains(
+    mut brain_events: EventReader<AssetEvent<DemonBrainDef>>,
+    brains: Res<Assets<DemonBrainDef>>,
+    mut query: Query<(&mut DemonBrain, &DemonArchetype)>,
+) {
+    for event in brain_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if let Some(brain_def) = brains.get(*id) {
+                for (mut brain, archetype) in query.iter_mut() {
+                    if archetype.brain.id() == *id {
+                        println!("Reloading {} brain", archetype.name);
+                        // The controller lives on the Demon, so the current task survives.
+                        brain.0 = brain_def.create_tree();
+                    }
+                }
+            }
+        }
+    }
+}
+
 pub fn untask_demons(
     mut query: Query<(&mut Demon, &mut Spine)>,
     mut events: EventReader<SpineEvent>,
+    distractions: Res<Distractions>,
 ) {
     for event in events.read() {
         if let SpineEvent::Complete {
@@ -345,24 +399,43 @@ pub fn untask_demons(
         } = event
         {
             if let Ok((mut demon, mut spine)) = query.get_mut(*entity) {
-                match animation.as_str() {
-                    "hit" => {
-                        if let DemonController::Distracted(Distraction::Annoyed, real_task) =
-                            &demon.action
+                if let DemonController::Distracted(distraction, real_task) = &demon.action {
+                    if let Some(def) = distractions.get(distraction) {
+                        if def.ends == DistractionEnd::AnimationComplete
+                            && def.animation == *animation
                         {
-                            demon.action = *real_task.clone();
+                            demon.action = def.resume_to(real_task);
                         }
                     }
-                    "walk" => {
-                        demon.action = DemonController::Idle;
-                    }
-                    _ => {}
+                } else if animation == "walk" {
+                    demon.action = DemonController::Idle;
                 }
             }
         }
     }
 }
 
+pub fn drop_demons(
+    mut drop_events: EventReader<DropEvent>,
+    mut demons: Query<(&mut Demon, &InRoom)>,
+    items: Query<(&DeskItem, &InRoom), With<DemonUsable>>,
+) {
+    for event in drop_events.read() {
+        if let Ok((mut demon, room)) = demons.get_mut(event.grabbed) {
+            // Tools in another room are out of reach.
+            demon.assigned_tool = event
+                .target
+                .and_then(|target| items.get(target).ok())
+                .filter(|(_, in_room)| *in_room == room)
+                .map(|(item, _)| *item);
+            if let Some(tool) = demon.assigned_tool {
+                println!("Assigning demon to {:?}", tool);
+                demon.action = DemonController::Idle;
+            }
+        }
+    }
+}
+
 pub fn bother_demons(
     mut query: Query<(&mut Demon, &mut Spine)>,
     mut events: EventReader<InteractEvent>,
@@ -379,24 +452,24 @@ pub fn bother_demons(
             if let Ok((mut demon, mut spine)) = query.get_mut(*entity) {
                 match &demon.action {
                     DemonController::Distracted(distraction, real_task) => {
-                        if distraction != &Distraction::Annoyed {
+                        if *distraction != Distraction::annoyed() {
                             demon.action = DemonController::Distracted(
-                                Distraction::Annoyed,
+                                Distraction::annoyed(),
                                 real_task.clone(),
                             );
                         }
                     }
                     DemonController::Introduce => {
                         demon.action = DemonController::Distracted(
-                            Distraction::Annoyed,
+                            Distraction::annoyed(),
                             Box::new(DemonController::Idle),
                         );
                     }
                     _ => {
                         demon.action = DemonController::Distracted(
-                            Distraction::Annoyed,
+                            Distraction::annoyed(),
                             Box::new(DemonController::Distracted(
-                                Distraction::Interrupted,
+                                Distraction::interrupted(),
                                 Box::new(demon.action.clone()),
                             )),
                         );
@@ -0,0 +1,188 @@
+use std::f32::consts::TAU;
+
+use bevy::audio::Volume;
+
+use crate::{game::strip_markup, prelude::*};
+
+use super::{get_characteristic_chance, voice_roll, DemonDna};
+
+const SAMPLE_RATE: u32 = 22050;
+const MAX_SYLLABLES: usize = 48;
+const HARMONICS: usize = 8;
+const VOICE_VOLUME: f32 = 0.45;
+
+// Gene indices for the voice, away from the ones distractions roll on.
+const PITCH_GENE: usize = 240;
+const SPEED_GENE: usize = 241;
+const TIMBRE_GENE: usize = 242;
+const WOBBLE_GENE: usize = 243;
+
+// The parts of a demon's voice that never change between lines.
+#[derive(Debug, Clone, Copy)]
+pub struct Voice {
+    pub pitch: f32,   // Base frequency in Hz.
+    pub speed: f32,   // Syllables per second.
+    pub formant: f32, // Scales the vowel formants, small demons sound small.
+    pub buzz: f32,    // 0 is a pure hum, 1 is a reedy buzz.
+    pub wobble: f32,  // How far the pitch wanders between syllables, in semitones.
+}
+
+impl Voice {
+    pub fn from_dna(dna: &DemonDna) -> Self {
+        let gene = |idx| get_characteristic_chance(dna, idx, 1.0);
+        let timbre = gene(TIMBRE_GENE);
+        Self {
+            pitch: 90.0 * 2f32.powf(gene(PITCH_GENE) * 2.0),
+            speed: 8.0 + gene(SPEED_GENE) * 8.0,
+            formant: 0.85 + timbre * 0.4,
+            buzz: (timbre * 7.0).fract(),
+            wobble: 1.0 + gene(WOBBLE_GENE) * 5.0,
+        }
+    }
+}
+
+struct Syllable {
+    vowel: char,
+    stressed: bool,
+}
+
+// Each run of vowels in a word is a syllable, and every word gets at least one.
+fn syllables(text: &str) -> Vec<Syllable> {
+    let mut syllables = Vec::new();
+    let text = strip_markup(text);
+    for word in text.split_whitespace() {
+        let mut found = 0;
+        let mut in_vowel = false;
+        for c in word.chars() {
+            let lower = c.to_ascii_lowercase();
+            let vowel = "aeiouy".contains(lower);
+            if vowel && !in_vowel {
+                syllables.push(Syllable {
+                    vowel: lower,
+                    stressed: c.is_uppercase(),
+                });
+                found += 1;
+            }
+            in_vowel = vowel;
+        }
+        if found == 0 && word.chars().any(|c| c.is_alphanumeric()) {
+            syllables.push(Syllable {
+                vowel: 'u',
+                stressed: false,
+            });
+        }
+        // Shouting stresses the last syllable of the word.
+        if word.ends_with('!') {
+            if let Some(last) = syllables.last_mut() {
+                last.stressed = true;
+            }
+        }
+    }
+    syllables.truncate(MAX_SYLLABLES);
+    syllables
+}
+
+// First and second formants, in Hz, for a grown-up human saying the vowel.
+fn formants(vowel: char) -> (f32, f32) {
+    match vowel {
+        'a' => (800.0, 1200.0),
+        'e' => (500.0, 1900.0),
+        'i' | 'y' => (300.0, 2300.0),
+        'o' => (500.0, 900.0),
+        _ => (350.0, 800.0),
+    }
+}
+
+fn harmonic_weights(voice: &Voice, pitch: f32, vowel: char) -> [f32; HARMONICS] {
+    let (f1, f2) = formants(vowel);
+    let (f1, f2) = (f1 * voice.formant, f2 * voice.formant);
+    let mut weights = [0.0; HARMONICS];
+    for (idx, weight) in weights.iter_mut().enumerate() {
+        let freq = pitch * (idx + 1) as f32;
+        let peak = |formant: f32| (-((freq - formant) / 250.0).powi(2)).exp();
+        // A buzzy voice keeps more of its upper harmonics.
+        let rolloff = 1.0 / (idx + 1) as f32;
+        *weight = rolloff * (1.0 - voice.buzz) + voice.buzz * 0.4 + peak(f1) + 0.6 * peak(f2);
+    }
+    let total: f32 = weights.iter().sum();
+    weights.map(|weight| weight / total)
+}
+
+/// Raw samples for the demon saying the text, one syllable per vowel sound.
+pub fn babble_samples(dna: &DemonDna, nonce: u32, text: &str) -> Vec<f32> {
+    let voice = Voice::from_dna(dna);
+    let syllables = syllables(text);
+    let question = strip_markup(text).trim_end().ends_with('?');
+    let mut samples = Vec::new();
+    let mut phase = [0.0f32; HARMONICS];
+    for (idx, syllable) in syllables.iter().enumerate() {
+        let roll = voice_roll(dna, nonce, idx as u32);
+        let step = (roll % 1000) as f32 / 1000.0 - 0.5;
+        let mut semitones = step * voice.wobble;
+        if question && idx + 1 == syllables.len() {
+            semitones += 4.0;
+        }
+        let pitch = voice.pitch * 2f32.powf(semitones / 12.0);
+        let length = (SAMPLE_RATE as f32 / voice.speed
+            * (0.8 + (roll >> 16) as f32 / 65535.0 * 0.4)) as usize;
+        let gap = length / 5;
+        let loudness = if syllable.stressed { 1.0 } else { 0.7 };
+        let weights = harmonic_weights(&voice, pitch, syllable.vowel);
+        for i in 0..length {
+            let t = i as f32 / length as f32;
+            // Quick attack and a softer release, so syllables don't click.
+            let envelope = (t * 12.0).min(1.0) * (1.0 - t).powf(0.6);
+            let mut sample = 0.0;
+            for harmonic in 0..HARMONICS {
+                let freq = pitch * (harmonic + 1) as f32;
+                phase[harmonic] = (phase[harmonic] + freq / SAMPLE_RATE as f32).fract();
+                sample += (phase[harmonic] * TAU).sin() * weights[harmonic];
+            }
+            samples.push(sample * envelope * loudness);
+        }
+        samples.extend(std::iter::repeat(0.0).take(gap));
+    }
+    samples
+}
+
+/// 16 bit mono WAV bytes, ready for an AudioSource or a file.
+pub fn encode_wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+pub fn babble_wav(dna: &DemonDna, nonce: u32, text: &str) -> Vec<u8> {
+    encode_wav(&babble_samples(dna, nonce, text))
+}
+
+pub fn play_babble(commands: &mut Commands, dna: DemonDna, nonce: u32, text: &str) {
+    let bytes = babble_wav(&dna, nonce, text);
+    commands.add(move |world: &mut World| {
+        let source = world
+            .resource_mut::<Assets<AudioSource>>()
+            .add(AudioSource {
+                bytes: bytes.into(),
+            });
+        world.spawn(AudioBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(VOICE_VOLUME)),
+        });
+    });
+}
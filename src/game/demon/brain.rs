@@ -1,16 +1,43 @@
-use crate::prelude::*;
+use std::cell::RefCell;
+
+use crate::{game::DeskLight, prelude::*};
 
 use behavior_bark::unpowered::*;
 use serde::{Deserialize, Serialize};
 
-use super::{pick_random_tool, roll_characteristic, tool_time, DemonDna};
+use super::{
+    pick_random_tool, roll_characteristic, tool_time, DemonDna, Distraction, Distractions,
+    PotionEffect,
+};
 
 pub struct DemonModel {
     pub nearest_tool: DeskItem,
+    pub assigned_tool: Option<DeskItem>, // Where the player dropped us, if anywhere.
+    pub potion: Option<PotionEffect>,
+    pub light: f32, // Desk light level, from 0 to 1.
     pub in_range_of_tool: bool,
     pub using_tool: Option<f32>,
     pub dna: DemonDna,
     pub nonce: u32, // Updates each time the tree is run fully.
+    pub distractions: Distractions,
+    pub trace: RefCell<Vec<(DemonBehavior, TraceState)>>, // Leaves visited this tick, in order.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceState {
+    Waiting,
+    Complete,
+    Failed,
+}
+
+impl From<&UnpoweredFunctionState> for TraceState {
+    fn from(state: &UnpoweredFunctionState) -> Self {
+        match state {
+            UnpoweredFunctionState::Waiting => TraceState::Waiting,
+            UnpoweredFunctionState::Complete => TraceState::Complete,
+            UnpoweredFunctionState::Failed => TraceState::Failed,
+        }
+    }
 }
 
 #[derive(Component, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -42,6 +69,23 @@ impl UnpoweredFunction for DemonBehavior {
         self: &mut Self,
         model: &Self::Model,
         controller: &mut Self::Controller,
+    ) -> UnpoweredFunctionState {
+        let state = self.resolve(model, controller);
+        model
+            .trace
+            .borrow_mut()
+            .push((self.clone(), TraceState::from(&state)));
+        state
+    }
+
+    fn reset(self: &mut Self, model: &Self::Model) {}
+}
+
+impl DemonBehavior {
+    fn resolve(
+        &mut self,
+        model: &DemonModel,
+        controller: &mut DemonController,
     ) -> UnpoweredFunctionState {
         match self {
             DemonBehavior::NotDistracted => {
@@ -52,12 +96,17 @@ impl UnpoweredFunction for DemonBehavior {
                 }
             }
             DemonBehavior::CheckDistraction(distraction) => {
-                if roll_characteristic(
-                    &model.dna,
-                    distraction.gene_idx(),
-                    model.nonce,
-                    distraction.chance_basis(),
-                ) {
+                let mut scale = model
+                    .potion
+                    .map(|potion| potion.chance_scale(&distraction.0))
+                    .unwrap_or(1.0);
+                if let Some(def) = model.distractions.get(distraction) {
+                    scale *= DeskLight(model.light).distraction_scale(def.darkness_scale);
+                }
+                if model
+                    .distractions
+                    .roll(&model.dna, distraction, model.nonce, scale)
+                {
                     UnpoweredFunctionState::Complete
                 } else {
                     UnpoweredFunctionState::Failed
@@ -69,6 +118,10 @@ impl UnpoweredFunction for DemonBehavior {
                 UnpoweredFunctionState::Complete
             }
             DemonBehavior::DoILikeNearestTool => {
+                // Being sent to a tool overrides taste, MoveToNearestTool heads for it.
+                if model.assigned_tool.is_some() {
+                    return UnpoweredFunctionState::Complete;
+                }
                 if model.nearest_tool == DeskItem::Summoning && model.nonce < 10 {
                     return UnpoweredFunctionState::Failed;
                 }
@@ -99,11 +152,21 @@ impl UnpoweredFunction for DemonBehavior {
                 }
             }
             DemonBehavior::MoveToNearestTool => {
+                if let Some(assigned) = model.assigned_tool {
+                    return if model.in_range_of_tool && model.nearest_tool == assigned {
+                        UnpoweredFunctionState::Complete
+                    } else {
+                        *controller = DemonController::MoveTo(assigned);
+                        UnpoweredFunctionState::Waiting
+                    };
+                }
                 *controller = DemonController::MoveTo(model.nearest_tool);
                 UnpoweredFunctionState::Complete
             }
             DemonBehavior::MoveToRandomTool => {
-                let tool = pick_random_tool(&model.dna, model.nonce);
+                let tool = model
+                    .assigned_tool
+                    .unwrap_or_else(|| pick_random_tool(&model.dna, model.nonce));
                 if model.in_range_of_tool && model.nearest_tool == tool {
                     UnpoweredFunctionState::Complete
                 } else {
@@ -113,42 +176,6 @@ impl UnpoweredFunction for DemonBehavior {
             }
         }
     }
-
-    fn reset(self: &mut Self, model: &Self::Model) {}
-}
-
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-pub enum Distraction {
-    Berate,
-    Complain,
-    Interrupted,
-    Sleep,
-    Annoyed,
-    Wander,
-}
-
-impl Distraction {
-    pub fn gene_idx(&self) -> usize {
-        match self {
-            Distraction::Complain => 255,
-            Distraction::Sleep => 254,
-            Distraction::Annoyed => 253,
-            Distraction::Wander => 252,
-            Distraction::Berate => unreachable!(),
-            Distraction::Interrupted => unreachable!(),
-        }
-    }
-
-    pub fn chance_basis(&self) -> f32 {
-        match self {
-            Distraction::Complain => 0.5,
-            Distraction::Sleep => 0.1,
-            Distraction::Annoyed => 0.9,
-            Distraction::Wander => 0.9,
-            Distraction::Berate => unreachable!(),
-            Distraction::Interrupted => unreachable!(),
-        }
-    }
 }
 
 pub type DemonBrainNode =
@@ -0,0 +1,279 @@
+use std::collections::VecDeque;
+
+use behavior_bark::unpowered::UnpoweredTreeDef;
+
+use crate::prelude::*;
+
+use super::{get_name, DemonArchetype, DemonBehavior, DemonBrainDef, DemonModel, TraceState};
+
+const TRACE_HISTORY: usize = 12;
+
+#[derive(Resource, Default)]
+pub struct BrainDebugger {
+    pub visible: bool,
+    pub selected: Option<Entity>,
+}
+
+#[derive(Component)]
+pub struct BrainDebugOverlay;
+
+#[derive(Component, Default)]
+pub struct BrainTrace {
+    pub tick: u32,
+    pub entries: Vec<(DemonBehavior, TraceState)>,
+    pub inputs: String,
+    pub controller: Option<DemonController>,
+    pub history: VecDeque<String>,
+    matched_tick: u32,
+    matched: Vec<(usize, TraceState)>,
+}
+
+impl BrainTrace {
+    pub fn record_tick(&mut self, model: &DemonModel, controller: &DemonController, now: f32) {
+        self.tick += 1;
+        self.entries = model.trace.take();
+        self.inputs = format!(
+            "nearest: {:?}\nin range: {}\nusing: {:?}\nnonce: {}",
+            model.nearest_tool, model.in_range_of_tool, model.using_tool, model.nonce
+        );
+        if let Some((behavior, state)) = self.entries.last() {
+            let line = format!("{:.1}s {:?} -> {:?}", now, behavior, state);
+            if self.history.back() != Some(&line) {
+                self.push_history(line);
+            }
+        }
+        self.observe_controller(controller, now);
+    }
+
+    pub fn observe_controller(&mut self, controller: &DemonController, now: f32) {
+        if self.controller.as_ref() != Some(controller) {
+            self.push_history(format!("{:.1}s now {:?}", now, controller));
+            self.controller = Some(controller.clone());
+        }
+    }
+
+    fn push_history(&mut self, line: String) {
+        self.history.push_back(line);
+        while self.history.len() > TRACE_HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    // Leaves can repeat in a tree, so walk them in order. A resumed tick picks up
+    // at whichever leaf was left waiting last time.
+    fn match_entries(&mut self, lines: &[BrainLine]) {
+        if self.matched_tick == self.tick {
+            return;
+        }
+        let mut cursor = match self.matched.last() {
+            Some((idx, TraceState::Waiting)) => *idx,
+            _ => 0,
+        };
+        let mut matched = Vec::new();
+        for (behavior, state) in self.entries.iter() {
+            let found = (0..lines.len())
+                .map(|offset| (cursor + offset) % lines.len())
+                .find(|idx| lines[*idx].leaf.as_ref() == Some(behavior));
+            if let Some(idx) = found {
+                matched.push((idx, *state));
+                cursor = idx;
+            }
+        }
+        self.matched = matched;
+        self.matched_tick = self.tick;
+    }
+}
+
+struct BrainLine {
+    depth: usize,
+    label: String,
+    parent: Option<usize>,
+    leaf: Option<DemonBehavior>,
+}
+
+fn flatten_brain(
+    def: &DemonBrainDef,
+    depth: usize,
+    parent: Option<usize>,
+    lines: &mut Vec<BrainLine>,
+) {
+    let idx = lines.len();
+    match def {
+        UnpoweredTreeDef::Selector(children) | UnpoweredTreeDef::Sequence(children) => {
+            let label = if matches!(def, UnpoweredTreeDef::Selector(_)) {
+                "Selector"
+            } else {
+                "Sequence"
+            };
+            lines.push(BrainLine {
+                depth,
+                label: label.to_string(),
+                parent,
+                leaf: None,
+            });
+            for child in children.iter() {
+                flatten_brain(child, depth + 1, Some(idx), lines);
+            }
+        }
+        UnpoweredTreeDef::User(behavior) => {
+            lines.push(BrainLine {
+                depth,
+                label: format!("{:?}", behavior),
+                parent,
+                leaf: Some(behavior.clone()),
+            });
+        }
+        _ => {
+            lines.push(BrainLine {
+                depth,
+                label: "...".to_string(),
+                parent,
+                leaf: None,
+            });
+        }
+    }
+}
+
+fn trace_color(state: Option<TraceState>) -> Color {
+    match state {
+        Some(TraceState::Waiting) => Color::srgb(1.0, 0.85, 0.2),
+        Some(TraceState::Complete) => Color::srgb(0.3, 0.9, 0.3),
+        Some(TraceState::Failed) => Color::srgb(0.9, 0.3, 0.3),
+        None => Color::srgb(0.6, 0.6, 0.6),
+    }
+}
+
+pub fn toggle_brain_debugger(
+    actions: Res<ActionState>,
+    mut debugger: ResMut<BrainDebugger>,
+    demons: Query<Entity, With<Demon>>,
+    mut interact_events: EventReader<InteractEvent>,
+) {
+    if actions.just_pressed(InputAction::ToggleDebugger) {
+        debugger.visible = !debugger.visible;
+    }
+    if !debugger.visible {
+        return;
+    }
+    for event in interact_events.read() {
+        if let InteractEvent {
+            interact_type: InteractType::Press,
+            interactable: Interactable::Demon,
+            entity,
+            ..
+        } = event
+        {
+            debugger.selected = Some(*entity);
+        }
+    }
+    let mut all: Vec<Entity> = demons.iter().collect();
+    all.sort();
+    if debugger
+        .selected
+        .map(|id| !all.contains(&id))
+        .unwrap_or(true)
+    {
+        debugger.selected = all.first().cloned();
+    }
+    if actions.just_pressed(InputAction::CycleDebugger) && !all.is_empty() {
+        let current = debugger
+            .selected
+            .and_then(|id| all.iter().position(|other| *other == id))
+            .unwrap_or(0);
+        debugger.selected = Some(all[(current + 1) % all.len()]);
+    }
+}
+
+pub fn draw_brain_debugger(
+    mut commands: Commands,
+    debugger: Res<BrainDebugger>,
+    mut overlay: Query<(&mut Text, &mut Visibility), With<BrainDebugOverlay>>,
+    mut demons: Query<(&Demon, &DemonArchetype, &mut BrainTrace)>,
+    brains: Res<Assets<DemonBrainDef>>,
+) {
+    if overlay.is_empty() {
+        commands.spawn((
+            TextBundle::from_section("", TextStyle::default())
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    left: Val::Px(8.0),
+                    ..Default::default()
+                })
+                .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+            BrainDebugOverlay,
+        ));
+        return;
+    }
+    let (mut text, mut visibility) = overlay.single_mut();
+    let selected = debugger
+        .selected
+        .and_then(|entity| demons.get_mut(entity).ok());
+    if !debugger.visible || selected.is_none() {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+    let (demon, archetype, mut trace) = selected.unwrap();
+
+    let mut lines = Vec::new();
+    if let Some(def) = brains.get(&archetype.brain) {
+        flatten_brain(def, 0, None, &mut lines);
+    }
+    trace.match_entries(&lines);
+
+    let mut states = vec![None; lines.len()];
+    for (idx, state) in trace.matched.iter().filter(|(idx, _)| *idx < lines.len()) {
+        states[*idx] = Some(*state);
+    }
+    let mut on_path = vec![false; lines.len()];
+    let mut path_node = trace
+        .matched
+        .last()
+        .map(|(idx, _)| *idx)
+        .filter(|idx| *idx < lines.len());
+    while let Some(idx) = path_node {
+        on_path[idx] = true;
+        path_node = lines[idx].parent;
+    }
+    let path_state = trace.matched.last().map(|(_, state)| *state);
+
+    let style = |color: Color| TextStyle {
+        color,
+        font_size: 12.,
+        ..Default::default()
+    };
+    let mut sections = vec![TextSection::new(
+        format!("{} ({})\n", get_name(&demon.dna), archetype.name),
+        style(Color::WHITE),
+    )];
+    for (idx, line) in lines.iter().enumerate() {
+        let state = if line.leaf.is_some() {
+            states[idx]
+        } else if on_path[idx] {
+            path_state
+        } else {
+            None
+        };
+        sections.push(TextSection::new(
+            format!(
+                "{}{}{}\n",
+                "  ".repeat(line.depth),
+                if on_path[idx] { "> " } else { "" },
+                line.label
+            ),
+            style(trace_color(state)),
+        ));
+    }
+    sections.push(TextSection::new(
+        format!("\n{}\ncontroller: {:?}\n\n", trace.inputs, demon.action),
+        style(Color::WHITE),
+    ));
+    for line in trace.history.iter() {
+        sections.push(TextSection::new(
+            format!("{}\n", line),
+            style(Color::srgb(0.7, 0.7, 0.9)),
+        ));
+    }
+    text.sections = sections;
+}
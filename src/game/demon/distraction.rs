@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+use super::{pick_characteristic, roll_characteristic, DemonDna, DemonGrammar};
+
+// Distractions the game forces on demons, so they must always be defined.
+const REQUIRED_DISTRACTIONS: [&'static str; 3] = ["annoyed", "berate", "interrupted"];
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Distraction(pub String);
+
+impl Distraction {
+    pub fn annoyed() -> Self {
+        Self("annoyed".to_string())
+    }
+
+    pub fn berate() -> Self {
+        Self("berate".to_string())
+    }
+
+    pub fn interrupted() -> Self {
+        Self("interrupted".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DistractionRoll {
+    pub gene_idx: usize,
+    pub chance_basis: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatSlot {
+    pub gene_idx: usize,
+    pub options: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum DistractionEnd {
+    Poked,             // Lasts until the player bothers the demon.
+    AnimationComplete, // Ends when the main animation finishes.
+    Timeout,           // Ends after `duration` seconds.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum DistractionResume {
+    PreviousTask,
+    Idle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DistractionDef {
+    pub name: String,
+    #[serde(default)]
+    pub roll: Option<DistractionRoll>,
+    pub animation: String,
+    #[serde(default)]
+    pub looping: bool,
+    #[serde(default)]
+    pub then_loop: Option<String>,
+    #[serde(default)]
+    pub speed: f32,
+    // How much likelier the roll gets as the room goes dark, 2.0 being three times as likely.
+    #[serde(default)]
+    pub darkness_scale: f32,
+    #[serde(default)]
+    pub chat: Vec<ChatSlot>,
+    #[serde(default)]
+    pub line: Option<String>, // Grammar symbol to say, used instead of `chat`.
+    #[serde(default)]
+    pub duration: Option<f32>,
+    pub ends: DistractionEnd,
+    pub resume: DistractionResume,
+}
+
+impl DistractionDef {
+    pub fn is_playing(&self, current: Option<&String>) -> bool {
+        current
+            .map(|current| *current == self.animation || Some(current) == self.then_loop.as_ref())
+            .unwrap_or(false)
+    }
+
+    pub fn get_chat(&self, dna: &DemonDna, nonce: u32, grammar: &DemonGrammar) -> Option<String> {
+        if let Some(line) = &self.line {
+            return Some(grammar.expand(line, dna, nonce));
+        }
+        if self.chat.is_empty() {
+            return None;
+        }
+        Some(
+            self.chat
+                .iter()
+                .map(|slot| {
+                    slot.options[pick_characteristic(dna, slot.gene_idx, slot.options.len())]
+                        .as_str()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn resume_to(&self, real_task: &DemonController) -> DemonController {
+        match self.resume {
+            DistractionResume::PreviousTask => real_task.clone(),
+            DistractionResume::Idle => DemonController::Idle,
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.animation.is_empty() {
+            errors.push("has no animation".to_string());
+        }
+        if let Some(roll) = &self.roll {
+            if !(0.0..=1.0).contains(&roll.chance_basis) {
+                errors.push(format!(
+                    "chance basis {} is not in 0..=1",
+                    roll.chance_basis
+                ));
+            }
+        }
+        if self.ends == DistractionEnd::Timeout && self.duration.is_none() {
+            errors.push("ends on Timeout but has no duration".to_string());
+        }
+        if self.speed < 0.0 {
+            errors.push("has a negative speed".to_string());
+        }
+        if self.darkness_scale < 0.0 {
+            errors.push("has a negative darkness scale".to_string());
+        } else if self.darkness_scale > 0.0 && self.roll.is_none() {
+            errors.push("has a darkness scale but is never rolled".to_string());
+        }
+        if self.line.is_some() && !self.chat.is_empty() {
+            errors.push("has both a line and chat slots".to_string());
+        }
+        for (idx, slot) in self.chat.iter().enumerate() {
+            if slot.options.is_empty() {
+                errors.push(format!("chat slot {} has no options", idx));
+            }
+        }
+        errors
+    }
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct DistractionDefs {
+    pub distractions: Vec<DistractionDef>,
+}
+
+/// Every problem with a set of definitions, as "name: problem" strings.
+pub fn validate_distractions(defs: &DistractionDefs) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut seen = Vec::new();
+    for def in defs.distractions.iter() {
+        if seen.contains(&&def.name) {
+            errors.push(format!("{}: defined more than once", def.name));
+        }
+        seen.push(&def.name);
+        for error in def.validate() {
+            errors.push(format!("{}: {}", def.name, error));
+        }
+    }
+    for required in REQUIRED_DISTRACTIONS.iter() {
+        if !defs.distractions.iter().any(|def| def.name == *required) {
+            errors.push(format!("{}: required but not defined", required));
+        }
+    }
+    errors
+}
+
+#[derive(Resource, Clone, Default)]
+pub struct Distractions(pub Arc<HashMap<String, DistractionDef>>);
+
+impl Distractions {
+    // Broken definitions are left out, so a demon can't trip over them later.
+    pub fn from_defs(defs: &DistractionDefs) -> Self {
+        let mut distractions = HashMap::new();
+        for def in defs.distractions.iter() {
+            if def.validate().is_empty() {
+                distractions.insert(def.name.clone(), def.clone());
+            }
+        }
+        Self(Arc::new(distractions))
+    }
+
+    pub fn get(&self, distraction: &Distraction) -> Option<&DistractionDef> {
+        self.0.get(&distraction.0)
+    }
+
+    pub fn roll(&self, dna: &DemonDna, distraction: &Distraction, nonce: u32, scale: f32) -> bool {
+        self.get(distraction)
+            .and_then(|def| def.roll.as_ref())
+            .map(|roll| roll_characteristic(dna, roll.gene_idx, nonce, roll.chance_basis * scale))
+            .unwrap_or(false)
+    }
+}
+
+pub fn sync_distractions(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<DistractionDefs>>,
+    defs: Res<Assets<DistractionDefs>>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                if let Some(defs) = defs.get(*id) {
+                    println!("Loading {} distractions", defs.distractions.len());
+                    for error in validate_distractions(defs) {
+                        println!("Bad distraction {}", error);
+                    }
+                    commands.insert_resource(Distractions::from_defs(defs));
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use std::sync::Arc;
+
+use bevy::utils::{HashMap, HashSet};
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+use super::{get_introduction, get_name, voice_roll, DemonDna};
+
+const MAX_DEPTH: usize = 16;
+
+// Tracery-style rules. "#symbol#" expands to one of the symbol's options, "#symbol.a.capitalize#"
+// applies modifiers to the result, and "[key:#symbol#]" expands once and remembers it as "key".
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+pub struct Grammar {
+    pub rules: HashMap<String, Vec<String>>,
+}
+
+impl Grammar {
+    pub fn expand(&self, symbol: &str, dna: &DemonDna, nonce: u32) -> String {
+        let mut expansion = Expansion {
+            grammar: self,
+            dna,
+            nonce,
+            picks: 0,
+            vars: HashMap::new(),
+        };
+        expansion.vars.insert("name".to_string(), get_name(dna));
+        expansion.expand_symbol(symbol, 0)
+    }
+
+    /// Symbols that are used somewhere but never defined.
+    pub fn validate(&self) -> Vec<String> {
+        let mut defined: HashSet<String> = self.rules.keys().cloned().collect();
+        defined.insert("name".to_string());
+        let mut errors = Vec::new();
+        for (symbol, options) in self.rules.iter() {
+            if options.is_empty() {
+                errors.push(format!("{} has no options", symbol));
+            }
+            for option in options.iter() {
+                for (key, _) in actions_in(option) {
+                    defined.insert(key);
+                }
+            }
+        }
+        for (symbol, options) in self.rules.iter() {
+            for option in options.iter() {
+                for used in symbols_in(option) {
+                    if !defined.contains(&used) {
+                        errors.push(format!("{} uses undefined #{}#", symbol, used));
+                    }
+                }
+            }
+        }
+        errors.sort();
+        errors.dedup();
+        errors
+    }
+}
+
+fn symbols_in(text: &str) -> Vec<String> {
+    text.split('#')
+        .skip(1)
+        .step_by(2)
+        .map(|tag| tag.split('.').next().unwrap_or("").to_string())
+        .filter(|symbol| !symbol.is_empty())
+        .collect()
+}
+
+fn actions_in(text: &str) -> Vec<(String, String)> {
+    let mut actions = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        rest = &rest[open + 1..];
+        if let Some(action) = parse_action(rest) {
+            actions.push((action.0.to_string(), action.1.to_string()));
+        }
+    }
+    actions
+}
+
+// "key:value]..." after an opening bracket. Chat markup like "[name]" has no colon, so it's left alone.
+fn parse_action(text: &str) -> Option<(&str, &str, usize)> {
+    let close = text.find(']')?;
+    let (key, value) = text[..close].split_once(':')?;
+    if key.is_empty() || key.contains(|c: char| c.is_whitespace()) {
+        return None;
+    }
+    Some((key, value, close))
+}
+
+fn symbol_salt(symbol: &str) -> u32 {
+    symbol.bytes().fold(2166136261u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(16777619)
+    })
+}
+
+fn apply_modifier(text: String, modifier: &str) -> String {
+    match modifier {
+        "capitalize" => {
+            let mut chars = text.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => text,
+            }
+        }
+        "upper" => text.to_uppercase(),
+        "a" => {
+            let vowel = text
+                .chars()
+                .next()
+                .map(|c| "aeiouAEIOU".contains(c))
+                .unwrap_or(false);
+            format!("{} {}", if vowel { "an" } else { "a" }, text)
+        }
+        "s" => {
+            if text.ends_with('s')
+                || text.ends_with('x')
+                || text.ends_with("ch")
+                || text.ends_with("sh")
+            {
+                format!("{}es", text)
+            } else if text.ends_with('y')
+                && !text
+                    .chars()
+                    .rev()
+                    .nth(1)
+                    .map(|c| "aeiou".contains(c))
+                    .unwrap_or(false)
+            {
+                format!("{}ies", &text[..text.len() - 1])
+            } else {
+                format!("{}s", text)
+            }
+        }
+        _ => text,
+    }
+}
+
+struct Expansion<'a> {
+    grammar: &'a Grammar,
+    dna: &'a DemonDna,
+    nonce: u32,
+    picks: u32,
+    vars: HashMap<String, String>,
+}
+
+impl<'a> Expansion<'a> {
+    // Each demon only reaches for its favourite half of a symbol's options, so it keeps a
+    // recognisable voice, while the nonce picks a different favourite from line to line.
+    fn choose(&mut self, symbol: &str, count: usize) -> usize {
+        let salt = symbol_salt(symbol);
+        let mut ranked: Vec<usize> = (0..count).collect();
+        ranked.sort_by_key(|idx| voice_roll(self.dna, salt, *idx as u32));
+        let favourites = (count + 1) / 2;
+        let roll = voice_roll(
+            self.dna,
+            salt,
+            self.nonce.wrapping_mul(7919).wrapping_add(self.picks),
+        );
+        self.picks += 1;
+        ranked[roll as usize % favourites]
+    }
+
+    fn expand_symbol(&mut self, symbol: &str, depth: usize) -> String {
+        if let Some(value) = self.vars.get(symbol) {
+            return value.clone();
+        }
+        let options = self.grammar.rules.get(symbol);
+        if depth > MAX_DEPTH || options.map(|options| options.is_empty()).unwrap_or(true) {
+            return format!("(({}))", symbol);
+        }
+        let options = options.unwrap();
+        let option = options[self.choose(symbol, options.len())].clone();
+        self.expand_text(&option, depth + 1)
+    }
+
+    fn expand_text(&mut self, text: &str, depth: usize) -> String {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(idx) = rest.find(|c| c == '#' || c == '[') {
+            out.push_str(&rest[..idx]);
+            let marker = rest.as_bytes()[idx];
+            rest = &rest[idx + 1..];
+            if marker == b'#' {
+                let end = rest.find('#').unwrap_or(rest.len());
+                let mut parts = rest[..end].split('.');
+                let symbol = parts.next().unwrap_or("");
+                let mut expanded = self.expand_symbol(symbol, depth);
+                for modifier in parts {
+                    expanded = apply_modifier(expanded, modifier);
+                }
+                out.push_str(&expanded);
+                rest = &rest[(end + 1).min(rest.len())..];
+            } else if let Some((key, value, close)) = parse_action(rest) {
+                let value = self.expand_text(value, depth + 1);
+                self.vars.insert(key.to_string(), value);
+                rest = &rest[close + 1..];
+            } else {
+                out.push('[');
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+#[derive(Resource, Clone, Default)]
+pub struct DemonGrammar(pub Arc<Grammar>);
+
+impl DemonGrammar {
+    pub fn expand(&self, symbol: &str, dna: &DemonDna, nonce: u32) -> String {
+        self.0.expand(symbol, dna, nonce)
+    }
+
+    pub fn introduce(&self, dna: &DemonDna, nonce: u32) -> String {
+        if self.0.rules.contains_key("introduction") {
+            self.expand("introduction", dna, nonce)
+        } else {
+            get_introduction(dna)
+        }
+    }
+}
+
+pub fn sync_grammar(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<Grammar>>,
+    grammars: Res<Assets<Grammar>>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                if let Some(grammar) = grammars.get(*id) {
+                    println!("Loading {} grammar symbols", grammar.rules.len());
+                    for error in grammar.validate() {
+                        println!("Bad grammar: {}", error);
+                    }
+                    commands.insert_resource(DemonGrammar(Arc::new(grammar.clone())));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grammar(rules: &[(&str, &[&str])]) -> Grammar {
+        Grammar {
+            rules: rules
+                .iter()
+                .map(|(symbol, options)| {
+                    (
+                        symbol.to_string(),
+                        options.iter().map(|option| option.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn dna() -> DemonDna {
+        DemonDna([7; 16])
+    }
+
+    #[test]
+    fn modifiers_change_the_expansion() {
+        let modify = |text: &str, modifier| apply_modifier(text.to_string(), modifier);
+        assert_eq!(modify("apple", "capitalize"), "Apple");
+        assert_eq!(modify("", "capitalize"), "");
+        assert_eq!(modify("imp", "upper"), "IMP");
+        assert_eq!(modify("owl", "a"), "an owl");
+        assert_eq!(modify("demon", "a"), "a demon");
+        assert_eq!(modify("demon", "s"), "demons");
+        assert_eq!(modify("box", "s"), "boxes");
+        assert_eq!(modify("witch", "s"), "witches");
+        assert_eq!(modify("fly", "s"), "flies");
+        assert_eq!(modify("day", "s"), "days");
+        assert_eq!(modify("imp", "sideways"), "imp");
+    }
+
+    #[test]
+    fn symbols_expand_with_modifiers_in_order() {
+        let grammar = grammar(&[
+            ("origin", &["#animal.a.capitalize# sat."]),
+            ("animal", &["owl"]),
+        ]);
+        assert_eq!(grammar.expand("origin", &dna(), 0), "An owl sat.");
+    }
+
+    #[test]
+    fn actions_remember_one_expansion() {
+        let grammar = grammar(&[
+            ("origin", &["[pet:#animal#]#pet# and #pet#"]),
+            ("animal", &["owl", "cat", "toad", "newt"]),
+        ]);
+        for nonce in 0..8 {
+            let line = grammar.expand("origin", &dna(), nonce);
+            let (first, second) = line.split_once(" and ").unwrap();
+            assert_eq!(first, second);
+        }
+    }
+
+    #[test]
+    fn name_and_chat_markup_come_through() {
+        let grammar = grammar(&[("origin", &["[name]#name#[/name] waves."])]);
+        assert_eq!(
+            grammar.expand("origin", &dna(), 0),
+            format!("[name]{}[/name] waves.", get_name(&dna()))
+        );
+    }
+
+    #[test]
+    fn missing_and_runaway_symbols_are_marked() {
+        let grammar = grammar(&[("origin", &["#nothing#"]), ("loop", &["#loop#"])]);
+        assert_eq!(grammar.expand("origin", &dna(), 0), "((nothing))");
+        assert_eq!(grammar.expand("loop", &dna(), 0), "((loop))");
+        assert_eq!(
+            grammar.validate(),
+            vec!["origin uses undefined #nothing#".to_string()]
+        );
+    }
+
+    #[test]
+    fn same_demon_and_nonce_say_the_same_thing() {
+        let grammar = grammar(&[
+            ("origin", &["#a# #b#"]),
+            ("a", &["x", "y", "z"]),
+            ("b", &["1", "2", "3"]),
+        ]);
+        assert_eq!(
+            grammar.expand("origin", &dna(), 3),
+            grammar.expand("origin", &dna(), 3)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use behavior_bark::unpowered::UnpoweredTreeDef;
+
+use super::{DemonBehavior, DemonBrainDef, Distraction, Distractions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct BrainLint {
+    pub level: LintLevel,
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Outcomes {
+    complete: bool,
+    failed: bool,
+    waiting: bool,
+}
+
+impl Outcomes {
+    fn new(complete: bool, failed: bool, waiting: bool) -> Self {
+        Self {
+            complete,
+            failed,
+            waiting,
+        }
+    }
+}
+
+// Keep in step with DemonBehavior::resolve.
+fn leaf_outcomes(behavior: &DemonBehavior) -> Outcomes {
+    match behavior {
+        DemonBehavior::NotDistracted => Outcomes::new(true, false, true),
+        DemonBehavior::CheckDistraction(_) => Outcomes::new(true, true, false),
+        DemonBehavior::Distraction(_) => Outcomes::new(true, false, false),
+        DemonBehavior::DoILikeNearestTool => Outcomes::new(true, true, false),
+        DemonBehavior::MoveToNearestTool => Outcomes::new(true, false, true),
+        DemonBehavior::MoveToRandomTool => Outcomes::new(true, false, true),
+        DemonBehavior::UseTool => Outcomes::new(true, true, true),
+    }
+}
+
+fn lint_distraction(
+    distraction: &Distraction,
+    rolled: bool,
+    distractions: &Distractions,
+    path: &str,
+    lints: &mut Vec<BrainLint>,
+) {
+    let message = match distractions.get(distraction) {
+        None => format!("{:?} is not a known distraction", distraction.0),
+        Some(def) if rolled && def.roll.is_none() => {
+            format!("{:?} cannot be rolled for", distraction.0)
+        }
+        _ => return,
+    };
+    lints.push(BrainLint {
+        level: LintLevel::Error,
+        path: path.to_string(),
+        message,
+    });
+}
+
+fn lint_leaf(
+    behavior: &DemonBehavior,
+    distractions: &Distractions,
+    path: &str,
+    lints: &mut Vec<BrainLint>,
+) {
+    match behavior {
+        DemonBehavior::CheckDistraction(distraction) => {
+            lint_distraction(distraction, true, distractions, path, lints)
+        }
+        DemonBehavior::Distraction(distraction) => {
+            lint_distraction(distraction, false, distractions, path, lints)
+        }
+        _ => {}
+    }
+}
+
+// The leaf a branch checks first. If it fails, the whole branch fails with it.
+fn guard(def: &DemonBrainDef) -> Option<&DemonBehavior> {
+    match def {
+        UnpoweredTreeDef::Sequence(children) => children.first().and_then(guard),
+        UnpoweredTreeDef::User(behavior) => Some(behavior),
+        _ => None,
+    }
+}
+
+// Guards that roll on the DNA and nonce give the same answer twice in one tick.
+fn is_deterministic(behavior: &DemonBehavior) -> bool {
+    matches!(
+        behavior,
+        DemonBehavior::CheckDistraction(_) | DemonBehavior::DoILikeNearestTool
+    )
+}
+
+// Whether a branch can only fail at its guard, so a second branch with the same guard never runs.
+fn fails_only_at_guard(def: &DemonBrainDef, distractions: &Distractions) -> bool {
+    match def {
+        UnpoweredTreeDef::Sequence(children) => children
+            .iter()
+            .skip(1)
+            .all(|child| !lint_node(child, distractions, String::new(), &mut Vec::new()).failed),
+        UnpoweredTreeDef::User(_) => true,
+        _ => false,
+    }
+}
+
+fn lint_node(
+    def: &DemonBrainDef,
+    distractions: &Distractions,
+    path: String,
+    lints: &mut Vec<BrainLint>,
+) -> Outcomes {
+    match def {
+        UnpoweredTreeDef::Sequence(children) => {
+            let mut outcomes = Outcomes::new(true, false, false);
+            for (idx, child) in children.iter().enumerate() {
+                let child_path = format!("{} > Sequence[{}]", path, idx);
+                if !outcomes.complete {
+                    lints.push(BrainLint {
+                        level: LintLevel::Warning,
+                        path: child_path,
+                        message: "unreachable, an earlier step never completes".to_string(),
+                    });
+                    continue;
+                }
+                let child = lint_node(child, distractions, child_path, lints);
+                outcomes.failed |= child.failed;
+                outcomes.waiting |= child.waiting;
+                outcomes.complete = child.complete;
+            }
+            if !outcomes.complete {
+                lints.push(BrainLint {
+                    level: LintLevel::Warning,
+                    path,
+                    message: "sequence can never complete".to_string(),
+                });
+            }
+            outcomes
+        }
+        UnpoweredTreeDef::Selector(children) => {
+            let mut outcomes = Outcomes::new(false, true, false);
+            for (idx, child) in children.iter().enumerate() {
+                let child_path = format!("{} > Selector[{}]", path, idx);
+                if !outcomes.failed {
+                    lints.push(BrainLint {
+                        level: LintLevel::Warning,
+                        path: child_path,
+                        message: "unreachable, an earlier branch never fails".to_string(),
+                    });
+                    continue;
+                }
+                let repeated = guard(child).filter(|behavior| {
+                    is_deterministic(behavior)
+                        && children[..idx].iter().any(|earlier| {
+                            guard(earlier) == Some(*behavior)
+                                && fails_only_at_guard(earlier, distractions)
+                        })
+                });
+                if let Some(behavior) = repeated {
+                    lints.push(BrainLint {
+                        level: LintLevel::Warning,
+                        path: child_path,
+                        message: format!(
+                            "unreachable, an earlier branch already checks {:?} and it rolls the same",
+                            behavior
+                        ),
+                    });
+                    continue;
+                }
+                let child = lint_node(child, distractions, child_path, lints);
+                outcomes.complete |= child.complete;
+                outcomes.waiting |= child.waiting;
+                outcomes.failed = child.failed;
+            }
+            outcomes
+        }
+        UnpoweredTreeDef::User(behavior) => {
+            let path = format!("{} > {:?}", path, behavior);
+            lint_leaf(behavior, distractions, &path, lints);
+            leaf_outcomes(behavior)
+        }
+        // Nodes we don't model could do anything.
+        _ => Outcomes::new(true, true, true),
+    }
+}
+
+pub fn lint_brain(def: &DemonBrainDef, distractions: &Distractions) -> Vec<BrainLint> {
+    let mut lints = Vec::new();
+    lint_node(def, distractions, "root".to_string(), &mut lints);
+    lints
+}
@@ -4,5 +4,17 @@ mod rand;
 pub use rand::*;
 mod brain;
 pub use brain::*;
+mod distraction;
+pub use distraction::*;
 mod actions;
 pub use actions::*;
+mod debug;
+pub use debug::*;
+mod lint;
+pub use lint::*;
+mod potion;
+pub use potion::*;
+mod grammar;
+pub use grammar::*;
+mod babble;
+pub use babble::*;
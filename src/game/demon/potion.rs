@@ -0,0 +1,186 @@
+use serde::Deserialize;
+
+use crate::{
+    game::{Inventory, ProductKind, ProductUsed},
+    prelude::*,
+};
+
+use super::get_potion_effect;
+
+const POTION_DURATION: f32 = 45.0;
+const ICON_OFFSET: Vec3 = Vec3::new(0.0, 40.0, 5.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum PotionEffect {
+    Euphoria,
+    Despair,
+    Sickness,
+    Mirth,
+}
+
+impl PotionEffect {
+    // How much more (or less) likely a distraction is while the potion lasts.
+    pub fn chance_scale(&self, distraction: &str) -> f32 {
+        match (self, distraction) {
+            (PotionEffect::Euphoria, "sleep") => 0.25,
+            (PotionEffect::Euphoria, "complain") => 0.5,
+            (PotionEffect::Despair, "complain") => 3.0,
+            (PotionEffect::Despair, "sleep") => 1.5,
+            (PotionEffect::Sickness, "sleep") => 2.0,
+            (PotionEffect::Sickness, "wander") => 0.5,
+            (PotionEffect::Mirth, "wander") => 2.0,
+            (PotionEffect::Mirth, "complain") => 0.5,
+            _ => 1.0,
+        }
+    }
+
+    // What a buyer will pay for it, compared to an ordinary potion.
+    pub fn value(&self) -> f32 {
+        match self {
+            PotionEffect::Euphoria => 1.5,
+            PotionEffect::Despair => 0.8,
+            PotionEffect::Sickness => 0.6,
+            PotionEffect::Mirth => 1.2,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PotionEffect::Euphoria => Color::srgb(1.0, 0.85, 0.2),
+            PotionEffect::Despair => Color::srgb(0.3, 0.35, 0.8),
+            PotionEffect::Sickness => Color::srgb(0.4, 0.8, 0.2),
+            PotionEffect::Mirth => Color::srgb(1.0, 0.4, 0.7),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PotionModifier {
+    pub effect: PotionEffect,
+    pub remaining: f32,
+}
+
+#[derive(Component)]
+pub struct PotionIcon(pub Entity);
+
+fn give_potion(
+    inventory: &mut Inventory,
+    used_events: &mut EventWriter<ProductUsed>,
+    demon: &mut Demon,
+    id: u32,
+) {
+    if let Some(potion) = inventory.take(id) {
+        used_events.send(ProductUsed(potion.clone()));
+        let effect = get_potion_effect(&potion.maker);
+        println!("Feeding demon a {:?} potion", effect);
+        demon.potion = Some(PotionModifier {
+            effect,
+            remaining: POTION_DURATION,
+        });
+    }
+}
+
+pub fn feed_potions(
+    mut commands: Commands,
+    skeletons: Res<Skeletons>,
+    mut drop_events: EventReader<DropEvent>,
+    mut context_events: EventReader<ContextAction>,
+    mut inventory: ResMut<Inventory>,
+    mut used_events: EventWriter<ProductUsed>,
+    mut demons: Query<&mut Demon>,
+) {
+    for event in drop_events.read() {
+        let is_potion = event
+            .product
+            .and_then(|id| inventory.get(id))
+            .map(|product| product.kind == ProductKind::Potion)
+            .unwrap_or(false);
+        if !is_potion {
+            continue;
+        }
+        if let Some(mut demon) = event.target.and_then(|target| demons.get_mut(target).ok()) {
+            give_potion(
+                &mut inventory,
+                &mut used_events,
+                &mut demon,
+                event.product.unwrap(),
+            );
+        }
+    }
+    for event in context_events.read() {
+        match event {
+            ContextAction::PressLeft(Contextable::Demon(entity)) => {
+                let potion = inventory
+                    .oldest(ProductKind::Potion)
+                    .map(|product| product.id);
+                if let (Some(id), Ok(mut demon)) = (potion, demons.get_mut(*entity)) {
+                    give_potion(&mut inventory, &mut used_events, &mut demon, id);
+                }
+            }
+            ContextAction::HoverLeft(chat_attach, Contextable::Demon(_)) => {
+                let text = match inventory.oldest(ProductKind::Potion) {
+                    Some(potion) => format!("Give them the [potion]{}[/potion].", potion.label()),
+                    None => "[warn]You have no potions to give.[/warn]".to_string(),
+                };
+                spawn_chat_box(
+                    &mut commands,
+                    skeletons.chat.clone(),
+                    ChatBox::info(*chat_attach, text),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn wear_off_potions(time: Res<Time>, mut demons: Query<&mut Demon>) {
+    for mut demon in demons.iter_mut() {
+        if let Some(potion) = demon.potion.as_mut() {
+            potion.remaining -= time.delta_seconds();
+            if potion.remaining <= 0.0 {
+                println!("Potion wore off");
+                demon.potion = None;
+            }
+        }
+    }
+}
+
+pub fn show_potion_icons(
+    mut commands: Commands,
+    demons: Query<(Entity, &Demon, &Transform), Without<PotionIcon>>,
+    mut icons: Query<(Entity, &PotionIcon, &mut Transform, &mut Sprite)>,
+) {
+    for (icon_entity, icon, mut transform, mut sprite) in icons.iter_mut() {
+        match demons.get(icon.0) {
+            Ok((_, demon, demon_transform)) if demon.potion.is_some() => {
+                let potion = demon.potion.unwrap();
+                transform.translation = demon_transform.translation + ICON_OFFSET;
+                // Fade out as it wears off.
+                sprite.color = potion
+                    .effect
+                    .color()
+                    .with_alpha((potion.remaining / 10.0).min(1.0));
+            }
+            _ => commands.entity(icon_entity).despawn_recursive(),
+        }
+    }
+    for (entity, demon, demon_transform) in demons.iter() {
+        if demon.potion.is_none() || icons.iter().any(|(_, icon, _, _)| icon.0 == entity) {
+            continue;
+        }
+        let mut transform = Transform::from_translation(demon_transform.translation + ICON_OFFSET);
+        transform.rotation = Quat::from_rotation_z(std::f32::consts::FRAC_PI_4);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: demon.potion.unwrap().effect.color(),
+                    custom_size: Some(Vec2::splat(8.0)),
+                    ..Default::default()
+                },
+                transform,
+                ..Default::default()
+            },
+            PotionIcon(entity),
+        ));
+    }
+}
@@ -1,6 +1,6 @@
-use crate::DeskItem;
+use crate::prelude::DeskItem;
 
-use super::DemonDna;
+use super::{DemonDna, PotionEffect};
 
 pub fn whisky2(i0: u32, i1: u32) -> u32 {
     /*
@@ -57,6 +57,15 @@ pub fn get_characteristic_chance(dna: &DemonDna, idx: usize, chance_basis: f32)
     characteristic * chance_basis
 }
 
+// A roll that stays the same for a given demon, salt and nonce.
+pub fn voice_roll(dna: &DemonDna, salt: u32, nonce: u32) -> u32 {
+    whisky2(characteristic(&dna.0, 0) ^ salt, nonce)
+}
+
+pub fn pick_characteristic(dna: &DemonDna, idx: usize, count: usize) -> usize {
+    characteristic(&dna.0, idx) as usize % count
+}
+
 pub fn roll_characteristic(dna: &DemonDna, idx: usize, nonce: u32, chance_basis: f32) -> bool {
     let chance = get_characteristic_chance(dna, idx, chance_basis);
     let characteristic = nonced_characteristic(&dna.0, idx, nonce);
@@ -83,6 +92,24 @@ pub fn tool_time(dna: &DemonDna, nonce: u32, min: f32, max: f32) -> f32 {
     min + (max - min) * characteristic
 }
 
+const BRAIN_ARCHETYPE_IDX: usize = 18;
+// Every archetype scores the DNA against its own name and the best score wins. Adding a brain
+// file only takes the demons it now wins, instead of reshuffling everyone like a modulo would.
+pub fn pick_archetype(dna: &DemonDna, names: &[String]) -> Option<usize> {
+    let dna_hash = characteristic(&dna.0, BRAIN_ARCHETYPE_IDX);
+    names
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, name)| {
+            // FNV-1a, so the score only depends on the name and not on the order.
+            let name_hash = name.bytes().fold(2166136261u32, |hash, byte| {
+                (hash ^ byte as u32).wrapping_mul(16777619)
+            });
+            whisky2(dna_hash, name_hash)
+        })
+        .map(|(idx, _)| idx)
+}
+
 // const HEAD_SKINS: [&'static str; 1] = ["debug/head"];
 // const BODY_SKINS: [&'static str; 1] = ["debug/torso"];
 // const HAND_SKINS: [&'static str; 1] = ["debug/hand"];
@@ -111,6 +138,19 @@ pub fn get_skins(dna: &DemonDna) -> [&'static str; 4] {
     [head_skin, body_skin, hand_skin, foot_skin]
 }
 
+const QUALITY_IDX: usize = 60;
+const QUALITY_NAMES: [&'static str; 4] = ["shoddy", "decent", "fine", "exquisite"];
+
+// How well a demon does its work, from 0.5 up to 2.
+pub fn get_quality(dna: &DemonDna) -> f32 {
+    0.5 + get_characteristic_chance(dna, QUALITY_IDX, 1.5)
+}
+
+pub fn get_quality_name(dna: &DemonDna) -> &'static str {
+    let idx = ((get_quality(dna) - 0.5) / 1.5 * QUALITY_NAMES.len() as f32) as usize;
+    QUALITY_NAMES[idx.min(QUALITY_NAMES.len() - 1)]
+}
+
 const POTION_TYPE_IDX: usize = 55; // These are the same, so they match up.
 const POTION_EMOTION_IDX: usize = 55;
 const POTION_AFTERTASTE_IDX: usize = 56;
@@ -148,12 +188,22 @@ pub fn get_potion_name(dna: &DemonDna) -> &'static str {
     POTION_TYPE[characteristic(&dna.0, POTION_TYPE_IDX) as usize % POTION_TYPE.len()]
 }
 
+pub fn get_potion_effect(dna: &DemonDna) -> PotionEffect {
+    // Follows the order of POTION_TYPE.
+    match characteristic(&dna.0, POTION_TYPE_IDX) % 4 {
+        0 => PotionEffect::Euphoria,
+        1 => PotionEffect::Despair,
+        2 => PotionEffect::Sickness,
+        _ => PotionEffect::Mirth,
+    }
+}
+
 pub fn get_potion(dna: &DemonDna) -> String {
     let emotion = POTION_EMOTIONS
         [characteristic(&dna.0, POTION_EMOTION_IDX) as usize % POTION_EMOTIONS.len()];
     let aftertaste = POTION_AFTERTASTES
         [characteristic(&dna.0, POTION_AFTERTASTE_IDX) as usize % POTION_AFTERTASTES.len()];
-    format!("{}{}", emotion, aftertaste)
+    format!("[potion]{}[/potion]{}", emotion, aftertaste)
 }
 
 const LORE_TYPE_IDX: usize = 6;
@@ -200,7 +250,10 @@ pub fn get_lore(dna: &DemonDna) -> String {
     let lore = LORES[characteristic(&dna.0, LORE_IDX) as usize % LORES.len()];
     let quality =
         LORE_QUALITIES[characteristic(&dna.0, LORE_QUALITY_IDX) as usize % LORE_QUALITIES.len()];
-    format!("{} has written {} {} {}", name, quantity, lore, quality)
+    format!(
+        "[name]{}[/name] has written {} [term]{}[/term] {}",
+        name, quantity, lore, quality
+    )
 }
 
 const INTRO_GREETING_IDX: usize = 9;
@@ -236,60 +289,5 @@ pub fn get_introduction(dna: &DemonDna) -> String {
     let name = INTRO_NAME[characteristic(&dna.0, INTRO_NAME_IDX) as usize % INTRO_NAME.len()];
     let stinger =
         INTRO_STINGER[characteristic(&dna.0, INTRO_STINGER_IDX) as usize % INTRO_STINGER.len()];
-    format!("{}{}{}", greeting, name, stinger)
-}
-
-const BERATE_EXPLETIVE_IDX: usize = 12;
-const BERATE_EXPLETIVE: [&'static str; 4] = ["Nerd!", "Dork!", "Geek!", "Dweeb!"];
-const BERATE_REASON_IDX: usize = 13;
-const BERATE_REASON: [&'static str; 4] = [
-    " You're not even trying!",
-    " Hurry up!",
-    "!!",
-    " You're wasting my time!",
-];
-
-pub fn get_berate(dna: &DemonDna) -> String {
-    let expletive = BERATE_EXPLETIVE
-        [characteristic(&dna.0, BERATE_EXPLETIVE_IDX) as usize % BERATE_EXPLETIVE.len()];
-    let reason =
-        BERATE_REASON[characteristic(&dna.0, BERATE_REASON_IDX) as usize % BERATE_REASON.len()];
-    format!("{}{}", expletive, reason)
-}
-
-const COMPLAIN_EXPLETIVE_IDX: usize = 14;
-const COMPLAIN_EXPLETIVE: [&'static str; 4] = ["Hey!", "Oi!", "Grrr!", "Argh!"];
-const COMPLAIN_REASON_IDX: usize = 15;
-const COMPLAIN_REASON: [&'static str; 4] = [
-    " This place is too cold, can't you turn up the heat!",
-    " I'm bored! Do something interesting!",
-    " I'm hungry! Maybe I should eat you!",
-    " I'm thirsty! Get me some water, you big oaf!",
-];
-
-pub fn get_complain(dna: &DemonDna) -> String {
-    let expletive = COMPLAIN_EXPLETIVE
-        [characteristic(&dna.0, COMPLAIN_EXPLETIVE_IDX) as usize % COMPLAIN_EXPLETIVE.len()];
-    let reason = COMPLAIN_REASON
-        [characteristic(&dna.0, COMPLAIN_REASON_IDX) as usize % COMPLAIN_REASON.len()];
-    format!("{}{}", expletive, reason)
-}
-
-const INTERRUPTED_EXPLETIVE_IDX: usize = 16;
-const INTERRUPTED_EXPLETIVE: [&'static str; 4] =
-    ["What's the big idea!?", "You big oaf!", "Jerk!", "Huh??"];
-const INTERRUPTED_REASON_IDX: usize = 17;
-const INTERRUPTED_REASON: [&'static str; 4] = [
-    " I was in the middle of something!",
-    " You're so rude!",
-    " I was just about to do something!",
-    " I was thinking!",
-];
-
-pub fn get_interrupted(dna: &DemonDna) -> String {
-    let expletive = INTERRUPTED_EXPLETIVE
-        [characteristic(&dna.0, INTERRUPTED_EXPLETIVE_IDX) as usize % INTERRUPTED_EXPLETIVE.len()];
-    let reason = INTERRUPTED_REASON
-        [characteristic(&dna.0, INTERRUPTED_REASON_IDX) as usize % INTERRUPTED_REASON.len()];
-    format!("{}{}", expletive, reason)
+    format!("{}[name]{}[/name]{}", greeting, name, stinger)
 }
@@ -2,11 +2,20 @@ use imp_encode::CursedConfig;
 
 use crate::prelude::*;
 
-use super::{get_name, get_skins, random_genes, DemonBrain, DemonBrainDef};
+use super::{
+    get_name, get_skins, pick_archetype, random_genes, BrainTrace, DemonBrain, DemonBrainDef,
+    PotionModifier,
+};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct DemonDna(pub [u8; 16]);
 
+#[derive(Component, Debug, Clone)]
+pub struct DemonArchetype {
+    pub name: String,
+    pub brain: Handle<DemonBrainDef>,
+}
+
 #[derive(Component)]
 pub struct Demon {
     pub dna: DemonDna,
@@ -14,7 +23,10 @@ pub struct Demon {
     pub action: DemonController,
     pub in_area_for_tool: Option<DeskItem>,
     pub nearest_tool: DeskItem,
-    pub chatting: Option<(&'static str)>,
+    pub assigned_tool: Option<DeskItem>,
+    pub potion: Option<PotionModifier>,
+    pub chatting: Option<String>,
+    pub distracted_for: f32,
     pub chat_attach: Option<Entity>,
 }
 
@@ -26,7 +38,10 @@ impl Demon {
             action: DemonController::Introduce,
             in_area_for_tool: None,
             nearest_tool: DeskItem::Summoning,
+            assigned_tool: None,
+            potion: None,
             chatting: None,
+            distracted_for: 0.,
             chat_attach: None,
         }
     }
@@ -40,10 +55,24 @@ pub fn spawn_demon(
     dna: Option<DemonDna>,
     brains: &Assets<DemonBrainDef>,
 ) {
-    let brains = brains.get(&game_assets.demon_brain).unwrap().create_tree();
+    let dna = dna.unwrap_or_else(random_genes);
+    // Brains that failed to load can't be picked.
+    let archetypes: Vec<(String, Handle<DemonBrainDef>)> = game_assets
+        .brain_archetypes()
+        .into_iter()
+        .filter(|(_, brain)| brains.contains(brain))
+        .collect();
+    let names: Vec<String> = archetypes.iter().map(|(name, _)| name.clone()).collect();
+    let picked = pick_archetype(&dna, &names);
+    if picked.is_none() {
+        println!("No demon brains loaded, check assets/brains for errors");
+        return;
+    }
+    let (archetype, brain) = archetypes[picked.unwrap()].clone();
+    println!("Spawning {} demon", archetype);
+    let brains = brains.get(&brain).unwrap().create_tree();
     let mut transform = Transform::from_translation(Vec3::new(position.x, position.y, 0.0));
     transform.scale = Vec3::splat(0.1);
-    let dna = dna.unwrap_or_else(random_genes);
     commands.spawn((
         SpineBundle {
             skeleton,
@@ -61,6 +90,11 @@ pub fn spawn_demon(
         GravityScale(0.0),
         Demon::from_dna(dna),
         DemonBrain(brains),
+        DemonArchetype {
+            name: archetype,
+            brain,
+        },
+        BrainTrace::default(),
         Interactable::Demon,
     ));
 }
@@ -0,0 +1,318 @@
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    ZoomIn,
+    ZoomOut,
+    Follow,
+    NextRoom,
+    Interact,
+    SecondaryInteract,
+    FocusUp,
+    FocusDown,
+    FocusLeft,
+    FocusRight,
+    Activate,
+    Menu,
+    Cancel,
+    ToggleShelf,
+    ToggleLog,
+    ToggleQuests,
+    ToggleShop,
+    Pause,
+    ToggleDebugger,
+    CycleDebugger,
+    OpenSettings,
+    ToggleFullscreen,
+}
+
+impl InputAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            InputAction::PanUp => "Pan up",
+            InputAction::PanDown => "Pan down",
+            InputAction::PanLeft => "Pan left",
+            InputAction::PanRight => "Pan right",
+            InputAction::ZoomIn => "Zoom in",
+            InputAction::ZoomOut => "Zoom out",
+            InputAction::Follow => "Follow demon",
+            InputAction::NextRoom => "Next room",
+            InputAction::Interact => "Interact",
+            InputAction::SecondaryInteract => "Secondary interact",
+            InputAction::FocusUp => "Focus up",
+            InputAction::FocusDown => "Focus down",
+            InputAction::FocusLeft => "Focus left",
+            InputAction::FocusRight => "Focus right",
+            InputAction::Activate => "Activate focused",
+            InputAction::Menu => "Open menu",
+            InputAction::Cancel => "Cancel",
+            InputAction::ToggleShelf => "Toggle shelf",
+            InputAction::ToggleLog => "Toggle chat log",
+            InputAction::ToggleQuests => "Toggle quests",
+            InputAction::ToggleShop => "Toggle shop",
+            InputAction::Pause => "Pause",
+            InputAction::ToggleDebugger => "Toggle brain debugger",
+            InputAction::CycleDebugger => "Cycle debugged demon",
+            InputAction::OpenSettings => "Settings",
+            InputAction::ToggleFullscreen => "Toggle fullscreen",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Mouse(button) => format!("Mouse {:?}", button),
+            Binding::Gamepad(button) => format!("Pad {:?}", button),
+        }
+    }
+
+    pub fn is_gamepad(&self) -> bool {
+        matches!(self, Binding::Gamepad(_))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionBindings {
+    pub action: InputAction,
+    pub bindings: Vec<Binding>,
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Serialize, Deserialize)]
+pub struct InputMapDef {
+    pub actions: Vec<ActionBindings>,
+}
+
+// The shipped controls.bindings holds the defaults, and the order actions are listed in. The
+// player's own bindings are kept in a separate file and laid over them.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct InputMap {
+    pub defaults: Vec<ActionBindings>,
+    pub bindings: HashMap<InputAction, Vec<Binding>>,
+}
+
+impl InputMap {
+    pub fn from_def(def: &InputMapDef) -> Self {
+        let mut map = Self {
+            defaults: def.actions.clone(),
+            bindings: HashMap::default(),
+        };
+        map.reset();
+        if let Some(user) = read_user_bindings() {
+            for entry in user.actions.into_iter() {
+                map.bindings.insert(entry.action, entry.bindings);
+            }
+        }
+        map
+    }
+
+    // Back to the shipped bindings, the player's file is only replaced on save.
+    pub fn reset(&mut self) {
+        self.bindings = self
+            .defaults
+            .iter()
+            .map(|entry| (entry.action, entry.bindings.clone()))
+            .collect();
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = InputAction> + '_ {
+        self.defaults.iter().map(|entry| entry.action)
+    }
+
+    pub fn to_def(&self) -> InputMapDef {
+        InputMapDef {
+            actions: self
+                .actions()
+                .map(|action| ActionBindings {
+                    action,
+                    bindings: self.get(action).to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, action: InputAction) -> &[Binding] {
+        self.bindings
+            .get(&action)
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Bindings shared by more than one action, with every action using them.
+    pub fn conflicts(&self) -> Vec<(Binding, Vec<InputAction>)> {
+        let mut users: Vec<(Binding, Vec<InputAction>)> = Vec::new();
+        for action in self.actions() {
+            for binding in self.get(action) {
+                if let Some((_, actions)) = users.iter_mut().find(|(other, _)| other == binding) {
+                    actions.push(action);
+                } else {
+                    users.push((*binding, vec![action]));
+                }
+            }
+        }
+        users.retain(|(_, actions)| actions.len() > 1);
+        users
+    }
+
+    pub fn save(&self) {
+        #[cfg(feature = "non-js")]
+        {
+            let pretty = ron::ser::PrettyConfig::default();
+            match ron::ser::to_string_pretty(&self.to_def(), pretty) {
+                Ok(text) => match std::fs::write(USER_BINDINGS_PATH, text) {
+                    Ok(_) => println!("Saved bindings to {}", USER_BINDINGS_PATH),
+                    Err(err) => println!("Failed to save bindings: {}", err),
+                },
+                Err(err) => println!("Failed to serialize bindings: {}", err),
+            }
+        }
+        #[cfg(not(feature = "non-js"))]
+        println!("Saving bindings isn't supported here");
+    }
+}
+
+// Next to session.ron, never over the shipped defaults in assets.
+#[cfg(feature = "non-js")]
+const USER_BINDINGS_PATH: &str = "controls.ron";
+
+fn read_user_bindings() -> Option<InputMapDef> {
+    #[cfg(feature = "non-js")]
+    {
+        let text = std::fs::read_to_string(USER_BINDINGS_PATH).ok()?;
+        match ron::de::from_str(&text) {
+            Ok(def) => Some(def),
+            Err(err) => {
+                println!("Failed to read {}: {}", USER_BINDINGS_PATH, err);
+                None
+            }
+        }
+    }
+    #[cfg(not(feature = "non-js"))]
+    None
+}
+
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+    just_released: HashSet<InputAction>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: InputAction) -> bool {
+        self.just_released.contains(&action)
+    }
+}
+
+// While rebinding, every press goes to the settings screen instead.
+#[derive(Resource, Default)]
+pub struct RebindState {
+    pub listening: Option<InputAction>,
+}
+
+pub fn update_action_state(
+    mut actions: ResMut<ActionState>,
+    input_map: Res<InputMap>,
+    rebind: Res<RebindState>,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+) {
+    actions.pressed.clear();
+    actions.just_pressed.clear();
+    actions.just_released.clear();
+    if rebind.listening.is_some() {
+        return;
+    }
+    for (action, bindings) in input_map.bindings.iter() {
+        for binding in bindings.iter() {
+            let (pressed, just_pressed, just_released) = match binding {
+                Binding::Key(key) => (
+                    kb_input.pressed(*key),
+                    kb_input.just_pressed(*key),
+                    kb_input.just_released(*key),
+                ),
+                Binding::Mouse(button) => (
+                    mouse_input.pressed(*button),
+                    mouse_input.just_pressed(*button),
+                    mouse_input.just_released(*button),
+                ),
+                Binding::Gamepad(button_type) => {
+                    let buttons: Vec<GamepadButton> = gamepads
+                        .iter()
+                        .map(|gamepad| GamepadButton::new(gamepad, *button_type))
+                        .collect();
+                    (
+                        gamepad_input.any_pressed(buttons.iter().cloned()),
+                        gamepad_input.any_just_pressed(buttons.iter().cloned()),
+                        gamepad_input.any_just_released(buttons.iter().cloned()),
+                    )
+                }
+            };
+            if pressed {
+                actions.pressed.insert(*action);
+            }
+            if just_pressed {
+                actions.just_pressed.insert(*action);
+            }
+            if just_released {
+                actions.just_released.insert(*action);
+            }
+        }
+    }
+}
+
+pub fn sync_input_map(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<InputMapDef>>,
+    defs: Res<Assets<InputMapDef>>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                if let Some(def) = defs.get(*id) {
+                    let input_map = InputMap::from_def(def);
+                    for (binding, actions) in input_map.conflicts() {
+                        println!("Binding {} is used by {:?}", binding.label(), actions);
+                    }
+                    commands.insert_resource(input_map);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn toggle_pause(actions: Res<ActionState>, mut time: ResMut<Time<Virtual>>) {
+    if actions.just_pressed(InputAction::Pause) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+}
@@ -22,7 +22,7 @@ pub struct ContextMenu {
 #[derive(Clone, Debug)]
 pub enum Contextable {
     Debug,
-    Demon,
+    Demon(Entity),
     DeskItem(DeskItem),
 }
 
@@ -0,0 +1,123 @@
+use crate::{game::DemonUsable, prelude::*};
+
+const DRAG_DISTANCE: f32 = 8.0;
+const TOOL_DROP_RANGE: f32 = 60.0;
+
+#[derive(Event, Debug)]
+pub struct DropEvent {
+    pub grabbed: Entity,
+    pub product: Option<u32>,
+    pub target: Option<Entity>,
+    pub location: Vec2,
+}
+
+pub fn drag_system(
+    mut interact_events: EventWriter<InteractEvent>,
+    mut drop_events: EventWriter<DropEvent>,
+    actions: Res<ActionState>,
+    mut interact_state: ResMut<InteractState>,
+    mut demons: Query<(&mut Transform, &mut Velocity), With<Demon>>,
+    tools: Query<(Entity, &GlobalTransform), With<DemonUsable>>,
+    interactables: Query<&Interactable>,
+) {
+    let mouse_world_location = interact_state.mouse_location;
+    let released = actions.just_released(InputAction::Interact);
+    if let Some(pending) = interact_state.pending.clone() {
+        if mouse_world_location.distance(pending.origin) > DRAG_DISTANCE {
+            interact_state.pending = None;
+            interact_state.grabbed = Some(pending.entity);
+            interact_state.grabbed_product = pending.product;
+            interact_events.send(InteractEvent {
+                entity: pending.entity,
+                mouse_world_location,
+                interact_type: InteractType::Grab,
+                interactable: pending.interactable,
+            });
+        } else if released {
+            // Never moved far enough, so it was a click after all.
+            interact_state.pending = None;
+            interact_events.send(InteractEvent {
+                entity: pending.entity,
+                mouse_world_location,
+                interact_type: InteractType::Press,
+                interactable: pending.interactable,
+            });
+        }
+        return;
+    }
+
+    if let Some(grabbed) = interact_state.grabbed {
+        interact_state.drop_target =
+            if let Ok((mut transform, mut velocity)) = demons.get_mut(grabbed) {
+                // Demons come along for the ride, and land on whatever tool is nearby.
+                transform.translation = mouse_world_location.extend(transform.translation.z);
+                velocity.linvel = Vec2::ZERO;
+                tools
+                    .iter()
+                    .map(|(entity, transform)| {
+                        (
+                            entity,
+                            transform
+                                .translation()
+                                .truncate()
+                                .distance(mouse_world_location),
+                        )
+                    })
+                    .filter(|(_, distance)| *distance < TOOL_DROP_RANGE)
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(entity, _)| entity)
+            } else {
+                interact_state.hovered
+            };
+
+        if released {
+            let target = interact_state.drop_target;
+            drop_events.send(DropEvent {
+                grabbed,
+                product: interact_state.grabbed_product,
+                target,
+                location: mouse_world_location,
+            });
+            if let Some((target, interactable)) =
+                target.and_then(|target| interactables.get(target).ok().map(|i| (target, i)))
+            {
+                interact_events.send(InteractEvent {
+                    entity: target,
+                    mouse_world_location,
+                    interact_type: InteractType::DropOver,
+                    interactable: interactable.clone(),
+                });
+            }
+            interact_state.grabbed = None;
+            interact_state.grabbed_product = None;
+            interact_state.drop_target = None;
+        }
+    }
+}
+
+pub fn draw_drop_target(
+    mut gizmos: Gizmos,
+    interact_state: Res<InteractState>,
+    transforms: Query<&GlobalTransform>,
+) {
+    if interact_state.grabbed.is_none() {
+        return;
+    }
+    if interact_state.grabbed_product.is_some() {
+        gizmos.circle_2d(
+            interact_state.mouse_location,
+            6.,
+            Color::srgb(0.8, 0.8, 1.0),
+        );
+    }
+    if let Some(target) = interact_state
+        .drop_target
+        .and_then(|target| transforms.get(target).ok())
+    {
+        gizmos.circle_2d(
+            target.translation().truncate(),
+            30.,
+            Color::srgb(1.0, 0.85, 0.2),
+        );
+    }
+}
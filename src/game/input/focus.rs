@@ -0,0 +1,181 @@
+use crate::{
+    game::{CurrentRoom, InRoom},
+    prelude::*,
+};
+
+use super::{ActionState, ContextMenu, InputAction};
+
+#[derive(Resource, Default)]
+pub struct FocusState {
+    pub focused: Option<Entity>,
+    pub anchor: Option<Vec2>, // Where focus last was, to pick up from after a menu opens.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FocusInput {
+    Move(Vec2),
+    Activate,
+    Menu,
+    Cancel,
+}
+
+fn read_focus_input(actions: &ActionState) -> Option<FocusInput> {
+    [
+        (InputAction::FocusUp, FocusInput::Move(Vec2::Y)),
+        (InputAction::FocusDown, FocusInput::Move(Vec2::NEG_Y)),
+        (InputAction::FocusLeft, FocusInput::Move(Vec2::NEG_X)),
+        (InputAction::FocusRight, FocusInput::Move(Vec2::X)),
+        (InputAction::Activate, FocusInput::Activate),
+        (InputAction::Menu, FocusInput::Menu),
+        (InputAction::Cancel, FocusInput::Cancel),
+    ]
+    .iter()
+    .find(|(action, _)| actions.just_pressed(*action))
+    .map(|(_, input)| *input)
+}
+
+fn focusable(interactable: &Interactable, menu_open: bool) -> bool {
+    match interactable {
+        // While a menu is open, only its items can be focused.
+        Interactable::ContextItem => menu_open,
+        Interactable::Backdrop | Interactable::ShelfSlot(_) => false,
+        _ => !menu_open,
+    }
+}
+
+// The closest candidate roughly in `direction`, favoring ones straight ahead.
+fn next_in_direction(from: Vec2, direction: Vec2, candidates: &[(Entity, Vec2)]) -> Option<Entity> {
+    candidates
+        .iter()
+        .filter_map(|(entity, position)| {
+            let offset = *position - from;
+            let ahead = offset.dot(direction);
+            if ahead <= 1.0 {
+                return None;
+            }
+            let aside = offset.perp_dot(direction).abs();
+            Some((*entity, ahead + aside * 2.0))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+pub fn navigate_focus(
+    mut commands: Commands,
+    mut interact_events: EventWriter<InteractEvent>,
+    mut focus: ResMut<FocusState>,
+    actions: Res<ActionState>,
+    current_room: Res<CurrentRoom>,
+    interactables: Query<(Entity, &GlobalTransform, &Interactable, Option<&InRoom>)>,
+    menus: Query<Entity, With<ContextMenu>>,
+    camera: Query<&GlobalTransform, With<Camera>>,
+) {
+    let input = read_focus_input(&actions);
+    if input.is_none() {
+        return;
+    }
+    let input = input.unwrap();
+    let menu_open = !menus.is_empty();
+    let candidates: Vec<(Entity, Vec2)> = interactables
+        .iter()
+        .filter(|(_, _, interactable, _)| focusable(interactable, menu_open))
+        // Other rooms are off screen, menus and anything else without a room stay reachable.
+        .filter(|(_, _, _, room)| room.map_or(true, |room| Some(room.0) == current_room.0))
+        .map(|(entity, transform, _, _)| (entity, transform.translation().truncate()))
+        .collect();
+    let current = focus
+        .focused
+        .and_then(|focused| candidates.iter().find(|(entity, _)| *entity == focused))
+        .cloned();
+
+    match input {
+        FocusInput::Move(direction) => {
+            let next = if let Some((_, position)) = current {
+                next_in_direction(position, direction, &candidates)
+            } else {
+                // Nothing focused, so start from whatever is nearest the anchor or the view.
+                let center = focus.anchor.unwrap_or_else(|| {
+                    camera
+                        .iter()
+                        .next()
+                        .map(|transform| transform.translation().truncate())
+                        .unwrap_or_default()
+                });
+                candidates
+                    .iter()
+                    .min_by(|(_, a), (_, b)| a.distance(center).total_cmp(&b.distance(center)))
+                    .map(|(entity, _)| *entity)
+            };
+            if next.is_none() {
+                return;
+            }
+            let next = next.unwrap();
+            if let Some((old, position)) = current {
+                if let Ok((_, _, interactable, _)) = interactables.get(old) {
+                    interact_events.send(InteractEvent {
+                        entity: old,
+                        mouse_world_location: position,
+                        interact_type: InteractType::Unhover,
+                        interactable: interactable.clone(),
+                    });
+                }
+            }
+            if let Ok((_, transform, interactable, _)) = interactables.get(next) {
+                interact_events.send(InteractEvent {
+                    entity: next,
+                    mouse_world_location: transform.translation().truncate(),
+                    interact_type: InteractType::Hover,
+                    interactable: interactable.clone(),
+                });
+                focus.anchor = Some(transform.translation().truncate());
+            }
+            focus.focused = Some(next);
+        }
+        FocusInput::Activate => {
+            if let Some((entity, position)) = current {
+                let (_, _, interactable, _) = interactables.get(entity).unwrap();
+                interact_events.send(InteractEvent {
+                    entity,
+                    mouse_world_location: position,
+                    interact_type: InteractType::Press,
+                    interactable: interactable.clone(),
+                });
+            }
+        }
+        FocusInput::Menu => {
+            if let Some((entity, position)) = current {
+                if let Ok((_, _, Interactable::Demon, _)) = interactables.get(entity) {
+                    interact_events.send(InteractEvent {
+                        entity,
+                        mouse_world_location: position,
+                        interact_type: InteractType::Press,
+                        interactable: Interactable::Contextable(Contextable::Demon(entity)),
+                    });
+                }
+            }
+        }
+        FocusInput::Cancel => {
+            for menu in menus.iter() {
+                commands.entity(menu).despawn_recursive();
+            }
+            focus.focused = None;
+        }
+    }
+}
+
+pub fn draw_focus(
+    mut gizmos: Gizmos,
+    focus: Res<FocusState>,
+    transforms: Query<&GlobalTransform, With<Interactable>>,
+) {
+    if let Some(transform) = focus
+        .focused
+        .and_then(|focused| transforms.get(focused).ok())
+    {
+        gizmos.circle_2d(
+            transform.translation().truncate(),
+            20.,
+            Color::srgb(0.4, 0.9, 1.0),
+        );
+    }
+}
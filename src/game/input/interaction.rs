@@ -1,12 +1,24 @@
 use crate::prelude::*;
 
-use super::Contextable;
+use super::{ActionState, Contextable, InputAction};
 
 #[derive(Resource, Default)]
 pub struct InteractState {
     pub mouse_location: Vec2,
     pub hovered: Option<Entity>,
     pub grabbed: Option<Entity>,
+    pub grabbed_product: Option<u32>,
+    pub pending: Option<PendingGrab>,
+    pub drop_target: Option<Entity>,
+}
+
+// A press on something draggable, not yet known to be a click or a drag.
+#[derive(Clone, Debug)]
+pub struct PendingGrab {
+    pub entity: Entity,
+    pub interactable: Interactable,
+    pub origin: Vec2,
+    pub product: Option<u32>,
 }
 
 #[derive(Component, Clone, Debug)]
@@ -19,6 +31,7 @@ pub enum Interactable {
     Potion,
     Doorway,
     Demon,
+    ShelfSlot(u32),
     Backdrop,
 }
 
@@ -36,6 +49,7 @@ pub enum InteractType {
     HoverWithGrabbed, // Hover, with a grabbed item.
     Unhover,          // Maybe grabbed, mouse out.
     Press,            // Mouse down, grab.
+    Grab,             // Pressed and dragged away.
     DropOver,         // Hover, with a grabbed release.
 }
 
@@ -47,6 +61,7 @@ impl Interactable {
             Interactable::Candle(_) | Interactable::DoorwayCandle(_) => 35,
             Interactable::Potion | Interactable::Journal | Interactable::Doorway => 30,
             Interactable::Contextable(_) => 25,
+            Interactable::ShelfSlot(_) => 0,
             Interactable::Backdrop => -100,
         }
     }
@@ -60,9 +75,20 @@ impl Interactable {
             Interactable::Doorway => (offset + Vec2::new(0., 50.)).length() < 35.0,
             Interactable::ContextItem => offset.length() < 50.0,
             Interactable::Candle(_) | Interactable::DoorwayCandle(_) => offset.length() < 15.0,
+            Interactable::ShelfSlot(_) => false,
             Interactable::Backdrop => true,
         }
     }
+
+    pub fn draggable(&self) -> bool {
+        matches!(
+            self,
+            Interactable::Demon
+                | Interactable::Potion
+                | Interactable::Doorway
+                | Interactable::ShelfSlot(_)
+        )
+    }
 }
 
 pub fn track_mouse(
@@ -90,12 +116,17 @@ pub fn track_mouse(
 pub fn interactable_system(
     mut interact_events: EventWriter<InteractEvent>,
     query: Query<(Entity, &GlobalTransform, &Interactable)>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    ui: Query<&Interaction>,
+    actions: Res<ActionState>,
     mut interact_state: ResMut<InteractState>,
 ) {
     let mouse_world_location = interact_state.mouse_location;
     let mut best: Option<(Entity, &Interactable)> = None;
     for (entity, transform, interactable) in query.iter() {
+        if Some(entity) == interact_state.grabbed {
+            // Can't drop something on itself.
+            continue;
+        }
         let offset = transform.translation().truncate() - mouse_world_location;
         if interactable.in_range(offset) {
             if let Some((_, best_interactable)) = best {
@@ -109,26 +140,40 @@ pub fn interactable_system(
     }
     if let Some((entity, interactable)) = best {
         let old_hover = interact_state.hovered;
-        let pressed = mouse_button_input.just_pressed(MouseButton::Left);
-        let released = mouse_button_input.just_released(MouseButton::Left);
+        let over_ui = ui
+            .iter()
+            .any(|interaction| *interaction != Interaction::None);
+        let pressed = actions.just_pressed(InputAction::Interact) && !over_ui;
         if pressed {
+            if interactable.draggable() {
+                // Grabbed or clicked, drag_system decides once the mouse moves or lets go.
+                interact_state.pending = Some(PendingGrab {
+                    entity,
+                    interactable: interactable.clone(),
+                    origin: mouse_world_location,
+                    product: None,
+                });
+            } else {
+                interact_events.send(InteractEvent {
+                    entity,
+                    mouse_world_location,
+                    interact_type: InteractType::Press,
+                    interactable: interactable.clone(),
+                });
+            }
+            interact_state.hovered = Some(entity);
+        } else if actions.just_pressed(InputAction::SecondaryInteract)
+            && !over_ui
+            && matches!(interactable, Interactable::Demon)
+        {
+            // Right-clicking a demon opens its context menu.
             interact_events.send(InteractEvent {
                 entity,
                 mouse_world_location,
                 interact_type: InteractType::Press,
-                interactable: interactable.clone(),
+                interactable: Interactable::Contextable(Contextable::Demon(entity)),
             });
             interact_state.hovered = Some(entity);
-            // interact_state.grabbed = Some(entity); // Set elsewhere, when appropriate.
-        } else if released {
-            interact_events.send(InteractEvent {
-                entity,
-                mouse_world_location,
-                interact_type: InteractType::DropOver,
-                interactable: interactable.clone(),
-            });
-            interact_state.hovered = None;
-            interact_state.grabbed = None;
         } else {
             interact_state.hovered = Some(entity);
         }
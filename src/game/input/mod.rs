@@ -1,4 +1,12 @@
+mod bindings;
 mod context;
+mod drag;
+mod focus;
 mod interaction;
+mod settings;
+pub use bindings::*;
 pub use context::*;
+pub use drag::*;
+pub use focus::*;
 pub use interaction::*;
+pub use settings::*;
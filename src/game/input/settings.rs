@@ -0,0 +1,265 @@
+use crate::{game::chat::ChatSettings, prelude::*};
+
+use super::{ActionState, Binding, InputAction, InputMap, RebindState};
+
+#[derive(Resource, Default)]
+pub struct SettingsState {
+    pub open: bool,
+}
+
+#[derive(Component)]
+pub struct SettingsPanel;
+
+#[derive(Component)]
+pub struct BindingButton(pub InputAction);
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsButton {
+    RevealSpeed,
+    AutoDismiss,
+    Save,
+    Reset,
+    Close,
+}
+
+pub fn toggle_settings(
+    actions: Res<ActionState>,
+    mut settings: ResMut<SettingsState>,
+    mut rebind: ResMut<RebindState>,
+) {
+    if actions.just_pressed(InputAction::OpenSettings) {
+        settings.open = !settings.open;
+    } else if actions.just_pressed(InputAction::Cancel) && settings.open {
+        settings.open = false;
+    }
+    if !settings.open && rebind.listening.is_some() {
+        rebind.listening = None;
+    }
+}
+
+// Takes the first press after a binding button was clicked.
+pub fn capture_binding(
+    mut rebind: ResMut<RebindState>,
+    mut input_map: ResMut<InputMap>,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+) {
+    if rebind.listening.is_none() {
+        return;
+    }
+    let action = rebind.listening.unwrap();
+    if kb_input.just_pressed(KeyCode::Escape) {
+        rebind.listening = None;
+        return;
+    }
+    let binding = kb_input
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            mouse_input
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepad_input
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Gamepad(button.button_type))
+        });
+    if let Some(binding) = binding {
+        // Replaces the binding for the same kind of device, so pads and keyboards stay bound.
+        let bindings = input_map.bindings.entry(action).or_default();
+        bindings.retain(|other| other.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+        rebind.listening = None;
+    }
+}
+
+pub fn draw_settings(
+    mut commands: Commands,
+    settings: Res<SettingsState>,
+    input_map: Res<InputMap>,
+    rebind: Res<RebindState>,
+    chat_settings: Res<ChatSettings>,
+    panels: Query<Entity, With<SettingsPanel>>,
+) {
+    if !settings.is_changed()
+        && !input_map.is_changed()
+        && !rebind.is_changed()
+        && !chat_settings.is_changed()
+    {
+        return;
+    }
+    for entity in panels.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !settings.open {
+        return;
+    }
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        font_size: 14.,
+        ..Default::default()
+    };
+    let conflicts = input_map.conflicts();
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    left: Val::Px(8.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    row_gap: Val::Px(2.0),
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0.05, 0.05, 0.1, 0.9).into(),
+                ..Default::default()
+            },
+            SettingsPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(
+                "Controls (click a row, then press the new binding)",
+                text_style.clone(),
+            ));
+            for (binding, actions) in conflicts.iter() {
+                let names: Vec<&str> = actions.iter().map(|action| action.label()).collect();
+                panel.spawn(TextBundle::from_section(
+                    format!("{} is used by {}", binding.label(), names.join(", ")),
+                    TextStyle {
+                        color: Color::srgb(1.0, 0.4, 0.4),
+                        ..text_style.clone()
+                    },
+                ));
+            }
+            for action in input_map.actions() {
+                let conflicted = conflicts
+                    .iter()
+                    .any(|(_, actions)| actions.contains(&action));
+                let bindings = if rebind.listening == Some(action) {
+                    "press a key or button...".to_string()
+                } else {
+                    let labels: Vec<String> = input_map
+                        .get(action)
+                        .iter()
+                        .map(|binding| binding.label())
+                        .collect();
+                    labels.join(" / ")
+                };
+                panel
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                                ..Default::default()
+                            },
+                            background_color: if conflicted {
+                                Color::srgb(0.5, 0.1, 0.1).into()
+                            } else {
+                                Color::srgb(0.15, 0.15, 0.25).into()
+                            },
+                            ..Default::default()
+                        },
+                        BindingButton(action),
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            format!("{}: {}", action.label(), bindings),
+                            text_style.clone(),
+                        ));
+                    });
+            }
+            panel
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(6.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|row| {
+                    let reveal = format!("Text speed: {:.0}/s", chat_settings.reveal_speed);
+                    let dismiss = format!(
+                        "Auto-dismiss: {}",
+                        if chat_settings.auto_dismiss {
+                            "on"
+                        } else {
+                            "off"
+                        }
+                    );
+                    for (button, label) in [
+                        (SettingsButton::RevealSpeed, reveal.as_str()),
+                        (SettingsButton::AutoDismiss, dismiss.as_str()),
+                        (SettingsButton::Save, "Save"),
+                        (SettingsButton::Reset, "Reset to defaults"),
+                        (SettingsButton::Close, "Close"),
+                    ] {
+                        let disabled = button == SettingsButton::Save && !conflicts.is_empty();
+                        row.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::all(Val::Px(4.0)),
+                                    ..Default::default()
+                                },
+                                background_color: if disabled {
+                                    Color::srgb(0.2, 0.2, 0.2).into()
+                                } else {
+                                    Color::srgb(0.3, 0.2, 0.15).into()
+                                },
+                                ..Default::default()
+                            },
+                            button,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                        });
+                    }
+                });
+        });
+}
+
+pub fn press_settings_buttons(
+    mut settings: ResMut<SettingsState>,
+    mut input_map: ResMut<InputMap>,
+    mut rebind: ResMut<RebindState>,
+    mut chat_settings: ResMut<ChatSettings>,
+    binding_buttons: Query<(&Interaction, &BindingButton), Changed<Interaction>>,
+    settings_buttons: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in binding_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            rebind.listening = Some(button.0);
+        }
+    }
+    for (interaction, button) in settings_buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            SettingsButton::RevealSpeed => {
+                // Cycles through a few speeds, the last being effectively instant.
+                chat_settings.reveal_speed = match chat_settings.reveal_speed as u32 {
+                    0..=20 => 40.0,
+                    21..=40 => 80.0,
+                    41..=80 => 1000.0,
+                    _ => 20.0,
+                };
+            }
+            SettingsButton::AutoDismiss => chat_settings.auto_dismiss = !chat_settings.auto_dismiss,
+            SettingsButton::Save => {
+                if input_map.conflicts().is_empty() {
+                    input_map.save();
+                } else {
+                    println!("Not saving bindings with conflicts");
+                }
+            }
+            SettingsButton::Reset => input_map.reset(),
+            SettingsButton::Close => settings.open = false,
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use serde::Deserialize;
+
+use crate::{
+    game::{get_name, get_potion_effect, get_potion_name, get_quality, get_quality_name, DemonDna},
+    prelude::*,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum ProductKind {
+    Potion,
+    LorePage,
+    CallingCard,
+}
+
+impl ProductKind {
+    pub fn from_interactable(interactable: &Interactable) -> Option<Self> {
+        match interactable {
+            Interactable::Potion => Some(ProductKind::Potion),
+            Interactable::Journal => Some(ProductKind::LorePage),
+            Interactable::Doorway => Some(ProductKind::CallingCard),
+            _ => None,
+        }
+    }
+
+    fn base_price(&self) -> f32 {
+        match self {
+            ProductKind::Potion => 8.0,
+            ProductKind::LorePage => 5.0,
+            ProductKind::CallingCard => 15.0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProductKind::Potion => "potion",
+            ProductKind::LorePage => "lore page",
+            ProductKind::CallingCard => "calling card",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Product {
+    pub id: u32,
+    pub kind: ProductKind,
+    pub maker: DemonDna,
+    pub made_at: f32,
+}
+
+impl Product {
+    pub fn label(&self) -> String {
+        let name = match self.kind {
+            ProductKind::Potion => get_potion_name(&self.maker).to_string(),
+            kind => kind.name().to_string(),
+        };
+        format!(
+            "{} {} by {}",
+            get_quality_name(&self.maker),
+            name,
+            get_name(&self.maker)
+        )
+    }
+
+    pub fn price(&self) -> u32 {
+        let value = match self.kind {
+            ProductKind::Potion => get_potion_effect(&self.maker).value(),
+            _ => 1.0,
+        };
+        (self.kind.base_price() * value * get_quality(&self.maker)).round() as u32
+    }
+}
+
+const STARTING_CANDLES: u32 = 5;
+
+#[derive(Resource)]
+pub struct Inventory {
+    next_id: u32,
+    pub products: Vec<Product>,
+    pub spare_candles: u32,
+    pub coins: u32,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            products: Vec::new(),
+            spare_candles: STARTING_CANDLES,
+            coins: 0,
+        }
+    }
+}
+
+impl Inventory {
+    pub fn add(&mut self, kind: ProductKind, maker: DemonDna, made_at: f32) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.products.push(Product {
+            id,
+            kind,
+            maker,
+            made_at,
+        });
+        id
+    }
+
+    pub fn count(&self, kind: ProductKind) -> usize {
+        self.products
+            .iter()
+            .filter(|product| product.kind == kind)
+            .count()
+    }
+
+    pub fn oldest(&self, kind: ProductKind) -> Option<&Product> {
+        self.products.iter().find(|product| product.kind == kind)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Product> {
+        self.products.iter().find(|product| product.id == id)
+    }
+
+    pub fn take(&mut self, id: u32) -> Option<Product> {
+        self.products
+            .iter()
+            .position(|product| product.id == id)
+            .map(|idx| self.products.remove(idx))
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct UseProduct(pub u32);
+
+// A product that has been taken off the shelf and used up.
+#[derive(Event, Debug, Clone)]
+pub struct ProductUsed(pub Product);
+
+#[derive(Resource, Default)]
+pub struct ShelfState {
+    pub open: bool,
+}
+
+#[derive(Component)]
+pub struct ShelfPanel;
+
+#[derive(Component)]
+pub struct ShelfSlot(pub u32);
+
+#[derive(Component)]
+pub struct SellButton(pub u32);
+
+// Clicking a tool on the desk uses its oldest product.
+pub fn quick_use_products(
+    mut interact_events: EventReader<InteractEvent>,
+    mut use_events: EventWriter<UseProduct>,
+    inventory: Res<Inventory>,
+) {
+    for event in interact_events.read() {
+        if event.interact_type != InteractType::Press {
+            continue;
+        }
+        if let Interactable::ShelfSlot(id) = event.interactable {
+            use_events.send(UseProduct(id));
+        } else if let Some(product) = ProductKind::from_interactable(&event.interactable)
+            .and_then(|kind| inventory.oldest(kind))
+        {
+            use_events.send(UseProduct(product.id));
+        }
+    }
+}
+
+// Dragging off a pile picks up its oldest product.
+pub fn grab_products(
+    mut interact_events: EventReader<InteractEvent>,
+    mut interact_state: ResMut<InteractState>,
+    inventory: Res<Inventory>,
+) {
+    for event in interact_events.read() {
+        if event.interact_type != InteractType::Grab || interact_state.grabbed_product.is_some() {
+            continue;
+        }
+        if let Some(kind) = ProductKind::from_interactable(&event.interactable) {
+            interact_state.grabbed_product = inventory.oldest(kind).map(|product| product.id);
+            if interact_state.grabbed_product.is_none() {
+                // Nothing on the pile to pick up.
+                interact_state.grabbed = None;
+            }
+        }
+    }
+}
+
+pub fn toggle_shelf(actions: Res<ActionState>, mut shelf: ResMut<ShelfState>) {
+    if actions.just_pressed(InputAction::ToggleShelf) {
+        shelf.open = !shelf.open;
+    }
+}
+
+pub fn draw_shelf(
+    mut commands: Commands,
+    shelf: Res<ShelfState>,
+    inventory: Res<Inventory>,
+    panels: Query<Entity, With<ShelfPanel>>,
+) {
+    if !shelf.is_changed() && !inventory.is_changed() {
+        return;
+    }
+    for entity in panels.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !shelf.open {
+        return;
+    }
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        font_size: 14.,
+        ..Default::default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    right: Val::Px(8.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    row_gap: Val::Px(4.0),
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0.1, 0.05, 0.05, 0.85).into(),
+                ..Default::default()
+            },
+            ShelfPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(
+                format!(
+                    "Shelf: {} potions, {} pages, {} cards, {} spare candles, {} coins",
+                    inventory.count(ProductKind::Potion),
+                    inventory.count(ProductKind::LorePage),
+                    inventory.count(ProductKind::CallingCard),
+                    inventory.spare_candles,
+                    inventory.coins,
+                ),
+                text_style.clone(),
+            ));
+            if inventory.products.is_empty() {
+                panel.spawn(TextBundle::from_section(
+                    "Nothing yet. Put your demons to work!",
+                    text_style.clone(),
+                ));
+            }
+            for product in inventory.products.iter() {
+                panel
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(4.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::all(Val::Px(4.0)),
+                                    ..Default::default()
+                                },
+                                background_color: Color::srgb(0.3, 0.2, 0.15).into(),
+                                ..Default::default()
+                            },
+                            ShelfSlot(product.id),
+                        ))
+                        .with_children(|button| {
+                            button.spawn(TextBundle::from_section(
+                                format!("{} ({:.0}s)", product.label(), product.made_at),
+                                text_style.clone(),
+                            ));
+                        });
+                        row.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::all(Val::Px(4.0)),
+                                    ..Default::default()
+                                },
+                                background_color: Color::srgb(0.2, 0.3, 0.15).into(),
+                                ..Default::default()
+                            },
+                            SellButton(product.id),
+                        ))
+                        .with_children(|button| {
+                            button.spawn(TextBundle::from_section(
+                                format!("Sell {}c", product.price()),
+                                text_style.clone(),
+                            ));
+                        });
+                    });
+            }
+        });
+}
+
+pub fn press_shelf_slots(
+    slots: Query<(Entity, &Interaction, &ShelfSlot), Changed<Interaction>>,
+    mut interact_state: ResMut<InteractState>,
+) {
+    for (entity, interaction, slot) in slots.iter() {
+        if *interaction == Interaction::Pressed {
+            interact_state.pending = Some(PendingGrab {
+                entity,
+                interactable: Interactable::ShelfSlot(slot.0),
+                origin: interact_state.mouse_location,
+                product: Some(slot.0),
+            });
+        }
+    }
+}
+
+pub fn sell_products(
+    buttons: Query<(&Interaction, &SellButton), Changed<Interaction>>,
+    mut inventory: ResMut<Inventory>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let Some(product) = inventory.take(button.0) {
+            let price = product.price();
+            println!("Sold {} for {} coins", product.label(), price);
+            inventory.coins += price;
+        }
+    }
+}
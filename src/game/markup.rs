@@ -0,0 +1,211 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::prelude::*;
+
+// Inline chat markup, like "[name]Allen[/name] has written [term]gossip[/term]".
+// Tags don't nest, and anything that isn't a known tag is left as written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkupTag {
+    Name,
+    Warn,
+    Potion,
+    Term,
+}
+
+impl MarkupTag {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(MarkupTag::Name),
+            "warn" => Some(MarkupTag::Warn),
+            "potion" => Some(MarkupTag::Potion),
+            "term" => Some(MarkupTag::Term),
+            _ => None,
+        }
+    }
+
+    pub fn style(&self, base: &TextStyle) -> TextStyle {
+        let color = match self {
+            MarkupTag::Name => Color::srgb(0.55, 0.1, 0.6),
+            MarkupTag::Warn => Color::srgb(0.8, 0.1, 0.1),
+            MarkupTag::Potion => Color::srgb(0.1, 0.55, 0.3),
+            MarkupTag::Term => Color::srgb(0.15, 0.3, 0.75),
+        };
+        TextStyle {
+            color,
+            ..base.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkupSpan {
+    pub tag: Option<MarkupTag>,
+    pub text: String,
+}
+
+pub fn parse_markup(text: &str) -> Vec<MarkupSpan> {
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut tag: Option<MarkupTag> = None;
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        current.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let close = rest.find(']');
+        let parsed = close.and_then(|close| {
+            let inner = &rest[1..close];
+            match inner.strip_prefix('/') {
+                Some(name) if tag.is_some() && MarkupTag::from_name(name) == tag => {
+                    Some((None, close))
+                }
+                None if tag.is_none() => {
+                    MarkupTag::from_name(inner).map(|found| (Some(found), close))
+                }
+                _ => None,
+            }
+        });
+        if let Some((next_tag, close)) = parsed {
+            if !current.is_empty() {
+                spans.push(MarkupSpan {
+                    tag,
+                    text: std::mem::take(&mut current),
+                });
+            }
+            tag = next_tag;
+            rest = &rest[close + 1..];
+        } else {
+            current.push('[');
+            rest = &rest[1..];
+        }
+    }
+    current.push_str(rest);
+    if !current.is_empty() {
+        spans.push(MarkupSpan { tag, text: current });
+    }
+    spans
+}
+
+/// The text as it reads on screen, without any tags.
+pub fn strip_markup(text: &str) -> String {
+    parse_markup(text)
+        .into_iter()
+        .map(|span| span.text)
+        .collect()
+}
+
+/// Styled sections for the first `limit` graphemes of the text.
+pub fn markup_sections(text: &str, base: &TextStyle, limit: usize) -> Vec<TextSection> {
+    let mut remaining = limit;
+    let mut sections = Vec::new();
+    for span in parse_markup(text) {
+        if remaining == 0 {
+            break;
+        }
+        let shown: String = span.text.graphemes(true).take(remaining).collect();
+        remaining -= shown.graphemes(true).count();
+        let style = span
+            .tag
+            .map(|tag| tag.style(base))
+            .unwrap_or_else(|| base.clone());
+        sections.push(TextSection::new(shown, style));
+    }
+    if sections.is_empty() {
+        sections.push(TextSection::new("", base.clone()));
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(tag: Option<MarkupTag>, text: &str) -> MarkupSpan {
+        MarkupSpan {
+            tag,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn tags_split_text_into_spans() {
+        assert_eq!(
+            parse_markup("[name]Allen[/name] has written [term]gossip[/term]."),
+            vec![
+                span(Some(MarkupTag::Name), "Allen"),
+                span(None, " has written "),
+                span(Some(MarkupTag::Term), "gossip"),
+                span(None, "."),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_tag_runs_to_the_end() {
+        assert_eq!(
+            parse_markup("Hello [name]Allen"),
+            vec![span(None, "Hello "), span(Some(MarkupTag::Name), "Allen")]
+        );
+        assert_eq!(strip_markup("Hello [name]Allen"), "Hello Allen");
+    }
+
+    #[test]
+    fn mismatched_close_tag_is_left_as_written() {
+        assert_eq!(
+            parse_markup("[name]Al[/term]len[/name]"),
+            vec![span(Some(MarkupTag::Name), "Al[/term]len")]
+        );
+        assert_eq!(
+            parse_markup("[/name]Allen"),
+            vec![span(None, "[/name]Allen")]
+        );
+    }
+
+    #[test]
+    fn nested_tags_are_left_as_written() {
+        assert_eq!(
+            parse_markup("[name]a [term]b[/term][/name]"),
+            vec![span(Some(MarkupTag::Name), "a [term]b[/term]")]
+        );
+    }
+
+    #[test]
+    fn brackets_that_are_not_tags_are_kept() {
+        assert_eq!(parse_markup("a [ b"), vec![span(None, "a [ b")]);
+        assert_eq!(parse_markup("[foo]bar"), vec![span(None, "[foo]bar")]);
+        assert_eq!(strip_markup("[[term]x[/term]"), "[x");
+        assert_eq!(parse_markup(""), vec![]);
+    }
+
+    #[test]
+    fn sections_stop_at_the_limit_across_spans() {
+        let base = TextStyle::default();
+        let sections = markup_sections("ab[term]cd[/term]", &base, 3);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].value, "ab");
+        assert_eq!(sections[0].style.color, base.color);
+        assert_eq!(sections[1].value, "c");
+        assert_eq!(sections[1].style.color, MarkupTag::Term.style(&base).color);
+    }
+
+    #[test]
+    fn sections_never_split_a_grapheme() {
+        let base = TextStyle::default();
+        // An accented letter made of two code points, then a family emoji joined from five.
+        let text = "[name]e\u{301}\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}x[/name]";
+        let sections = markup_sections(text, &base, 2);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(
+            sections[0].value,
+            "e\u{301}\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}"
+        );
+        assert_eq!(markup_sections(text, &base, 1)[0].value, "e\u{301}");
+    }
+
+    #[test]
+    fn empty_sections_still_give_one_to_draw() {
+        let base = TextStyle::default();
+        let sections = markup_sections("[term]gossip[/term]", &base, 0);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].value, "");
+    }
+}
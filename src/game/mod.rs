@@ -1,31 +1,100 @@
+pub mod audio;
 pub mod backdrop;
 pub mod camera;
 pub mod chat;
+pub mod chat_log;
 pub mod demon;
 pub mod input;
+pub mod inventory;
+pub mod markup;
+pub mod quests;
+pub mod session;
+pub mod shop;
+
+use bevy::input::InputSystem;
 
 use crate::prelude::*;
 
+use audio::*;
 use backdrop::*;
 use camera::*;
 use chat::*;
+use chat_log::*;
 use demon::*;
 use input::*;
+use inventory::*;
+use markup::*;
+use quests::*;
+use session::*;
+use shop::*;
 
 pub struct DeskPlugin;
 
 impl Plugin for DeskPlugin {
     fn build(&self, app: &mut App) {
+        register_desk_workstations(app);
         app.add_event::<InteractEvent>()
             .add_event::<ContextAction>()
+            .add_event::<UseProduct>()
+            .add_event::<DropEvent>()
+            .add_event::<SoundEvent>()
+            .add_event::<JobCompleted>()
+            .add_event::<ProductUsed>()
+            .init_resource::<Inventory>()
+            .init_resource::<ShelfState>()
             .init_resource::<InteractState>()
+            .init_resource::<FocusState>()
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<RebindState>()
+            .init_resource::<SettingsState>()
+            .init_resource::<MainChatQueue>()
+            .init_resource::<ChatSettings>()
+            .init_resource::<ChatLog>()
+            .init_resource::<ChatLogState>()
+            .add_systems(Startup, load_session)
+            .add_systems(Last, save_session)
+            .init_resource::<BrainDebugger>()
+            .init_resource::<Distractions>()
+            .init_resource::<DemonGrammar>()
+            .init_resource::<SoundCues>()
+            .init_resource::<CurrentRoom>()
+            .init_resource::<QuestBook>()
+            .init_resource::<QuestLog>()
+            .init_resource::<QuestPanelState>()
+            .init_resource::<Shop>()
+            .init_resource::<ShopState>()
+            .init_resource::<Upgrades>()
+            .add_systems(
+                Update,
+                (
+                    sync_distractions,
+                    sync_grammar,
+                    sync_input_map,
+                    sync_sound_cues,
+                    sync_quests,
+                    sync_shop,
+                ),
+            )
+            .add_systems(PreUpdate, update_action_state.after(InputSystem))
             .add_systems(OnEnter(Playing), spawn_camera)
             .add_systems(OnExit(Playing), despawn_camera)
             .add_systems(
                 OnEnter(Playing),
                 (add_backdrop_interactable, spawn_debug_item, spawn_desk),
             )
-            .add_systems(Update, zoom_and_move_camera.run_if(in_state(Playing)))
+            .add_systems(
+                Update,
+                (
+                    zoom_and_move_camera,
+                    toggle_camera_follow,
+                    frame_on_double_click,
+                    keep_focus_in_view.after(navigate_focus),
+                    ease_camera,
+                )
+                    .chain()
+                    .run_if(in_state(Playing)),
+            )
             .add_systems(
                 Update,
                 (
@@ -38,7 +107,15 @@ impl Plugin for DeskPlugin {
                     trigger_summoning,
                     trigger_doorway_summoning,
                     manage_chat_boxes,
+                    show_main_chat,
+                    type_chat_text,
+                    toggle_chat_log,
+                    draw_chat_log,
+                    scroll_chat_log,
                     light_candle,
+                    burn_candles,
+                    sync_candle_bones,
+                    show_darkness,
                 )
                     .run_if(in_state(Playing)),
             )
@@ -49,22 +126,91 @@ impl Plugin for DeskPlugin {
                     mark_closest_item,
                     mark_demons_in_area,
                     control_demons,
+                    reload_demon_brains,
                     activate_demons,
                     bother_demons,
                     untask_demons,
-                    trigger_doorway,
                     read_card,
-                    trigger_alembic,
+                    run_workstations,
                     drink_potion,
-                    trigger_journal,
                     despawn_demon_chat_boxes,
                     read_page,
+                    feed_potions,
+                    wear_off_potions,
+                    show_potion_icons,
+                )
+                    .run_if(in_state(Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    track_mouse,
+                    interactable_system,
+                    drag_system,
+                    draw_drop_target,
+                    navigate_focus,
+                    draw_focus,
+                )
+                    .chain()
+                    .run_if(in_state(Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    quick_use_products,
+                    grab_products,
+                    summon_from_card,
+                    drop_demons,
+                    toggle_shelf,
+                    draw_shelf,
+                    press_shelf_slots,
+                    show_potion_water,
                 )
                     .run_if(in_state(Playing)),
             )
             .add_systems(
                 Update,
-                (track_mouse, interactable_system).run_if(in_state(Playing)),
+                (
+                    toggle_settings,
+                    capture_binding,
+                    press_settings_buttons,
+                    draw_settings,
+                    toggle_pause,
+                    toggle_fullscreen,
+                    scale_ui,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (cue_spine_sounds, play_sounds)
+                    .chain()
+                    .run_if(in_state(Playing)),
+            )
+            .add_systems(
+                Update,
+                (track_quests, toggle_quest_panel, draw_quest_panel)
+                    .chain()
+                    .run_if(in_state(Playing)),
+            )
+            .add_systems(
+                Update,
+                (toggle_shop, press_shop_buttons, draw_shop, sell_products)
+                    .chain()
+                    .run_if(in_state(Playing)),
+            )
+            .add_systems(
+                Update,
+                (toggle_brain_debugger, draw_brain_debugger)
+                    .chain()
+                    .run_if(in_state(Playing)),
+            )
+            .add_systems(
+                Update,
+                (assign_rooms, travel_through_doorways, switch_rooms)
+                    .chain()
+                    .before(ease_camera)
+                    .run_if(in_state(Playing)),
             );
     }
 }
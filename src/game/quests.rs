@@ -0,0 +1,349 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+use super::{
+    get_potion_effect, log_chat, spawn_main_chat_box, DeskItemState, Inventory, JobCompleted,
+    PotionEffect, ProductKind, ProductUsed,
+};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Goal {
+    Make {
+        product: ProductKind,
+        #[serde(default)]
+        effect: Option<PotionEffect>, // Only counts potions with this effect.
+    },
+    Use {
+        product: ProductKind,
+    },
+    Summon,
+    WorkingAtOnce, // Progress is the most demons seen at their tools together.
+}
+
+impl Goal {
+    pub fn describe(&self, count: u32) -> String {
+        match self {
+            Goal::Make {
+                product,
+                effect: Some(effect),
+            } => format!("Make {} x {:?} {}", count, effect, product.name()),
+            Goal::Make { product, .. } => format!("Make {} x {}", count, product.name()),
+            Goal::Use { product } => format!("Use {} x {}", count, product.name()),
+            Goal::Summon => format!("Summon {} demons", count),
+            Goal::WorkingAtOnce => format!("Have {} demons working at once", count),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ObjectiveDef {
+    pub goal: Goal,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum Reward {
+    SpareCandles(u32),
+    Coins(u32),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuestDef {
+    pub id: String,
+    pub title: String,
+    pub objectives: Vec<ObjectiveDef>,
+    #[serde(default)]
+    pub rewards: Vec<Reward>,
+    #[serde(default)]
+    pub unlocks: Vec<String>,
+    #[serde(default)]
+    pub start: bool, // Active from the beginning, rather than unlocked by another quest.
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+pub struct QuestDefs {
+    pub quests: Vec<QuestDef>,
+}
+
+pub fn validate_quests(defs: &QuestDefs) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (idx, def) in defs.quests.iter().enumerate() {
+        if defs.quests[..idx].iter().any(|other| other.id == def.id) {
+            errors.push(format!("{} is defined twice", def.id));
+        }
+        if def.objectives.is_empty() {
+            errors.push(format!("{} has no objectives", def.id));
+        }
+        if def.objectives.iter().any(|objective| objective.count == 0) {
+            errors.push(format!("{} has an objective that needs nothing", def.id));
+        }
+        for unlock in def.unlocks.iter() {
+            if !defs.quests.iter().any(|other| other.id == *unlock) {
+                errors.push(format!("{} unlocks unknown quest {}", def.id, unlock));
+            }
+        }
+    }
+    errors
+}
+
+#[derive(Resource, Clone, Default)]
+pub struct QuestBook(pub Arc<QuestDefs>);
+
+impl QuestBook {
+    pub fn get(&self, id: &str) -> Option<&QuestDef> {
+        self.0.quests.iter().find(|def| def.id == id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuestProgress {
+    pub id: String,
+    pub counts: Vec<u32>,
+}
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct QuestLog {
+    pub active: Vec<QuestProgress>,
+    pub completed: Vec<String>,
+}
+
+impl QuestLog {
+    pub fn start(&mut self, def: &QuestDef) {
+        let known = self.completed.contains(&def.id)
+            || self.active.iter().any(|progress| progress.id == def.id);
+        if !known {
+            self.active.push(QuestProgress {
+                id: def.id.clone(),
+                counts: vec![0; def.objectives.len()],
+            });
+        }
+    }
+}
+
+pub fn sync_quests(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<QuestDefs>>,
+    defs: Res<Assets<QuestDefs>>,
+    mut log: ResMut<QuestLog>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                if let Some(defs) = defs.get(*id) {
+                    println!("Loading {} quests", defs.quests.len());
+                    for error in validate_quests(defs) {
+                        println!("Bad quest {}", error);
+                    }
+                    for def in defs.quests.iter().filter(|def| def.start) {
+                        log.start(def);
+                    }
+                    // Objectives may have been added or removed by a reload.
+                    for progress in log.active.iter_mut() {
+                        if let Some(def) = defs.quests.iter().find(|def| def.id == progress.id) {
+                            progress.counts.resize(def.objectives.len(), 0);
+                        }
+                    }
+                    commands.insert_resource(QuestBook(Arc::new(defs.clone())));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn track_quests(
+    mut commands: Commands,
+    mut job_events: EventReader<JobCompleted>,
+    mut used_events: EventReader<ProductUsed>,
+    summoned: Query<(), Added<Demon>>,
+    tools: Query<&DeskItemState>,
+    book: Res<QuestBook>,
+    mut log: ResMut<QuestLog>,
+    mut inventory: ResMut<Inventory>,
+) {
+    let jobs: Vec<&JobCompleted> = job_events.read().collect();
+    let used: Vec<&ProductUsed> = used_events.read().collect();
+    let summons = summoned.iter().count() as u32;
+    let working = tools.iter().filter(|state| state.user.is_some()).count() as u32;
+    if jobs.is_empty() && used.is_empty() && summons == 0 && working == 0 {
+        return;
+    }
+
+    let mut finished = Vec::new();
+    for idx in 0..log.active.len() {
+        let def = book.get(&log.active[idx].id);
+        if def.is_none() {
+            continue;
+        }
+        let def = def.unwrap();
+        let old_counts = &log.active[idx].counts;
+        let counts: Vec<u32> = def
+            .objectives
+            .iter()
+            .zip(old_counts.iter())
+            .map(|(objective, count)| {
+                let reached = match &objective.goal {
+                    Goal::Make { product, effect } => {
+                        count
+                            + jobs
+                                .iter()
+                                .filter(|job| job.product == *product)
+                                .filter(|job| {
+                                    effect.is_none()
+                                        || *effect == Some(get_potion_effect(&job.maker))
+                                })
+                                .count() as u32
+                    }
+                    Goal::Use { product } => {
+                        count + used.iter().filter(|used| used.0.kind == *product).count() as u32
+                    }
+                    Goal::Summon => count + summons,
+                    Goal::WorkingAtOnce => (*count).max(working),
+                };
+                reached.min(objective.count)
+            })
+            .collect();
+        // Only touch the log when something moved, so the panel isn't redrawn every frame.
+        if counts != *old_counts {
+            log.active[idx].counts = counts.clone();
+        }
+        let done = def
+            .objectives
+            .iter()
+            .zip(counts.iter())
+            .all(|(objective, count)| *count >= objective.count);
+        if done {
+            finished.push(def.clone());
+        }
+    }
+
+    for def in finished {
+        log.active.retain(|progress| progress.id != def.id);
+        log.completed.push(def.id.clone());
+        let mut text = format!("Quest complete: [term]{}[/term]", def.title);
+        for reward in def.rewards.iter() {
+            match reward {
+                Reward::SpareCandles(count) => {
+                    inventory.spare_candles += count;
+                    text.push_str(&format!(" (+{} spare candles)", count));
+                }
+                Reward::Coins(count) => {
+                    inventory.coins += count;
+                    text.push_str(&format!(" (+{} coins)", count));
+                }
+            }
+        }
+        println!("Completed quest {}", def.id);
+        log_chat(&mut commands, "Quests".to_string(), text.clone());
+        spawn_main_chat_box(&mut commands, "info", text);
+        for unlock in def.unlocks.iter() {
+            if let Some(next) = book.get(unlock) {
+                log.start(next);
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct QuestPanelState {
+    pub open: bool,
+}
+
+#[derive(Component)]
+pub struct QuestPanel;
+
+pub fn toggle_quest_panel(actions: Res<ActionState>, mut state: ResMut<QuestPanelState>) {
+    if actions.just_pressed(InputAction::ToggleQuests) {
+        state.open = !state.open;
+    }
+}
+
+pub fn draw_quest_panel(
+    mut commands: Commands,
+    state: Res<QuestPanelState>,
+    book: Res<QuestBook>,
+    log: Res<QuestLog>,
+    panels: Query<Entity, With<QuestPanel>>,
+) {
+    if !state.is_changed() && !log.is_changed() && !book.is_changed() {
+        return;
+    }
+    for entity in panels.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !state.open {
+        return;
+    }
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        font_size: 14.,
+        ..Default::default()
+    };
+    let done_style = TextStyle {
+        color: Color::srgb(0.5, 0.8, 0.5),
+        ..text_style.clone()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(8.0),
+                    right: Val::Px(8.0),
+                    width: Val::Px(300.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    row_gap: Val::Px(2.0),
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0.05, 0.08, 0.05, 0.85).into(),
+                ..Default::default()
+            },
+            QuestPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(
+                format!("Quests ({} complete)", log.completed.len()),
+                text_style.clone(),
+            ));
+            if log.active.is_empty() {
+                panel.spawn(TextBundle::from_section(
+                    "Nothing left to do. For now.",
+                    text_style.clone(),
+                ));
+            }
+            for progress in log.active.iter() {
+                let def = book.get(&progress.id);
+                if def.is_none() {
+                    continue;
+                }
+                let def = def.unwrap();
+                panel.spawn(TextBundle::from_section(
+                    def.title.clone(),
+                    TextStyle {
+                        color: Color::srgb(0.9, 0.7, 0.4),
+                        ..text_style.clone()
+                    },
+                ));
+                for (objective, count) in def.objectives.iter().zip(progress.counts.iter()) {
+                    let style = if *count >= objective.count {
+                        done_style.clone()
+                    } else {
+                        text_style.clone()
+                    };
+                    panel.spawn(TextBundle::from_section(
+                        format!(
+                            "  {} ({}/{})",
+                            objective.goal.describe(objective.count),
+                            count,
+                            objective.count
+                        ),
+                        style,
+                    ));
+                }
+            }
+        });
+}
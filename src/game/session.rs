@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+use super::ChatLog;
+
+#[cfg(feature = "non-js")]
+const SESSION_PATH: &str = "session.ron";
+
+// Everything carried over from one run of the game to the next.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Session {
+    pub chat_log: ChatLog,
+}
+
+fn read_session() -> Option<Session> {
+    #[cfg(feature = "non-js")]
+    {
+        let text = std::fs::read_to_string(SESSION_PATH).ok()?;
+        match ron::de::from_str(&text) {
+            Ok(session) => Some(session),
+            Err(err) => {
+                println!("Failed to read {}: {}", SESSION_PATH, err);
+                None
+            }
+        }
+    }
+    #[cfg(not(feature = "non-js"))]
+    None
+}
+
+pub fn load_session(mut commands: Commands) {
+    let mut session = read_session().unwrap_or_default();
+    session.chat_log.session += 1;
+    println!("Starting session {}", session.chat_log.session);
+    commands.insert_resource(session.chat_log);
+}
+
+pub fn save_session(mut exits: EventReader<AppExit>, chat_log: Res<ChatLog>) {
+    if exits.read().next().is_none() {
+        return;
+    }
+    let session = Session {
+        chat_log: chat_log.clone(),
+    };
+    #[cfg(feature = "non-js")]
+    {
+        let pretty = ron::ser::PrettyConfig::default();
+        match ron::ser::to_string_pretty(&session, pretty) {
+            Ok(text) => match std::fs::write(SESSION_PATH, text) {
+                Ok(_) => println!("Saved session to {}", SESSION_PATH),
+                Err(err) => println!("Failed to save session: {}", err),
+            },
+            Err(err) => println!("Failed to serialize session: {}", err),
+        }
+    }
+    #[cfg(not(feature = "non-js"))]
+    println!(
+        "Saving {} log entries isn't supported here",
+        session.chat_log.entries.len()
+    );
+}
@@ -0,0 +1,335 @@
+use std::sync::Arc;
+
+use bevy::utils::HashSet;
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+use super::{spawn_main_chat_box, CurrentRoom, DemonUsable, DeskItemState, InRoom, Inventory};
+
+// Where bought lanterns go on the desk, in order, relative to its middle.
+const LANTERN_SPOTS: [Vec2; 3] = [
+    Vec2::new(-220.0, -160.0),
+    Vec2::new(240.0, -170.0),
+    Vec2::new(-40.0, 190.0),
+];
+// Where bought tools go, out of the way of the desk's own.
+const TOOL_SPOTS: [Vec2; 2] = [Vec2::new(-320.0, -60.0), Vec2::new(330.0, -40.0)];
+// How close a demon has to stand to use a bought tool.
+const TOOL_REACH: f32 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Upgrade {
+    FasterTools,   // Demons work a quarter faster.
+    LongerCandles, // Candles burn half again as long.
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum ShopGood {
+    Candles(u32),
+    Lantern,
+    Tool(DeskItem), // Another desk item demons can work at, set down in the room on screen.
+    Upgrade(Upgrade),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShopEntry {
+    pub name: String,
+    pub price: u32,
+    pub good: ShopGood,
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+pub struct ShopDefs {
+    pub entries: Vec<ShopEntry>,
+}
+
+#[derive(Resource, Clone, Default)]
+pub struct Shop(pub Arc<ShopDefs>);
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Upgrades(pub HashSet<Upgrade>);
+
+impl Upgrades {
+    pub fn has(&self, upgrade: Upgrade) -> bool {
+        self.0.contains(&upgrade)
+    }
+
+    pub fn work_speed(&self) -> f32 {
+        if self.has(Upgrade::FasterTools) {
+            1.25
+        } else {
+            1.0
+        }
+    }
+
+    pub fn burn_time_scale(&self) -> f32 {
+        if self.has(Upgrade::LongerCandles) {
+            1.5
+        } else {
+            1.0
+        }
+    }
+}
+
+// A bought light that never burns out.
+#[derive(Component)]
+pub struct Lantern;
+
+// A desk item bought from the shop rather than laid out with the desk.
+#[derive(Component)]
+pub struct BoughtTool;
+
+// Bought things go in the room on screen, so that's the one running out of space.
+fn in_current_room<T: Component>(bought: &Query<&InRoom, With<T>>, current: &CurrentRoom) -> usize {
+    bought
+        .iter()
+        .filter(|room| Some(room.0) == current.0)
+        .count()
+}
+
+pub fn sync_shop(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<ShopDefs>>,
+    defs: Res<Assets<ShopDefs>>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                if let Some(defs) = defs.get(*id) {
+                    println!("Loading {} shop entries", defs.entries.len());
+                    commands.insert_resource(Shop(Arc::new(defs.clone())));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ShopState {
+    pub open: bool,
+}
+
+#[derive(Component)]
+pub struct ShopPanel;
+
+#[derive(Component)]
+pub struct ShopButton(pub usize);
+
+// Why an entry can't be bought right now, if it can't.
+fn unavailable(
+    entry: &ShopEntry,
+    inventory: &Inventory,
+    upgrades: &Upgrades,
+    lanterns: usize,
+    tools: usize,
+) -> Option<&'static str> {
+    match entry.good {
+        ShopGood::Upgrade(upgrade) if upgrades.has(upgrade) => Some("owned"),
+        ShopGood::Lantern if lanterns >= LANTERN_SPOTS.len() => Some("no space"),
+        ShopGood::Tool(_) if tools >= TOOL_SPOTS.len() => Some("no space"),
+        _ if inventory.coins < entry.price => Some("too dear"),
+        _ => None,
+    }
+}
+
+pub fn toggle_shop(actions: Res<ActionState>, mut state: ResMut<ShopState>) {
+    if actions.just_pressed(InputAction::ToggleShop) {
+        state.open = !state.open;
+    } else if actions.just_pressed(InputAction::Cancel) && state.open {
+        state.open = false;
+    }
+}
+
+pub fn draw_shop(
+    mut commands: Commands,
+    state: Res<ShopState>,
+    shop: Res<Shop>,
+    inventory: Res<Inventory>,
+    upgrades: Res<Upgrades>,
+    current: Res<CurrentRoom>,
+    lanterns: Query<&InRoom, With<Lantern>>,
+    tools: Query<&InRoom, With<BoughtTool>>,
+    panels: Query<Entity, With<ShopPanel>>,
+) {
+    if !state.is_changed()
+        && !shop.is_changed()
+        && !inventory.is_changed()
+        && !upgrades.is_changed()
+        && !current.is_changed()
+    {
+        return;
+    }
+    for entity in panels.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !state.open {
+        return;
+    }
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        font_size: 14.,
+        ..Default::default()
+    };
+    let lantern_count = in_current_room(&lanterns, &current);
+    let tool_count = in_current_room(&tools, &current);
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    left: Val::Percent(40.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    row_gap: Val::Px(4.0),
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0.1, 0.08, 0.02, 0.9).into(),
+                ..Default::default()
+            },
+            ShopPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(
+                format!("Shop: you have {} coins", inventory.coins),
+                text_style.clone(),
+            ));
+            for (idx, entry) in shop.0.entries.iter().enumerate() {
+                let reason = unavailable(entry, &inventory, &upgrades, lantern_count, tool_count);
+                let label = match reason {
+                    Some(reason) => format!("{} ({}c, {})", entry.name, entry.price, reason),
+                    None => format!("{} ({}c)", entry.name, entry.price),
+                };
+                panel
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(4.0)),
+                                ..Default::default()
+                            },
+                            background_color: if reason.is_some() {
+                                Color::srgb(0.2, 0.2, 0.2).into()
+                            } else {
+                                Color::srgb(0.35, 0.25, 0.1).into()
+                            },
+                            ..Default::default()
+                        },
+                        ShopButton(idx),
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(label, text_style.clone()));
+                    });
+            }
+        });
+}
+
+pub fn press_shop_buttons(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &ShopButton), Changed<Interaction>>,
+    shop: Res<Shop>,
+    mut inventory: ResMut<Inventory>,
+    mut upgrades: ResMut<Upgrades>,
+    current: Res<CurrentRoom>,
+    lanterns: Query<&InRoom, With<Lantern>>,
+    tools: Query<&InRoom, With<BoughtTool>>,
+    desks: Query<&Transform, With<Desk>>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let entry = shop.0.entries.get(button.0);
+        if entry.is_none() {
+            continue;
+        }
+        let entry = entry.unwrap();
+        if current.0.is_none() {
+            // No desk set up yet to put things on.
+            continue;
+        }
+        let lantern_count = in_current_room(&lanterns, &current);
+        let tool_count = in_current_room(&tools, &current);
+        if unavailable(entry, &inventory, &upgrades, lantern_count, tool_count).is_some() {
+            continue;
+        }
+        inventory.coins -= entry.price;
+        let room = current.0.unwrap();
+        let center = desks
+            .get(room)
+            .map(|transform| transform.translation.truncate())
+            .unwrap_or_default();
+        match &entry.good {
+            ShopGood::Candles(count) => inventory.spare_candles += count,
+            ShopGood::Lantern => {
+                spawn_lantern(&mut commands, room, center + LANTERN_SPOTS[lantern_count]);
+            }
+            ShopGood::Tool(item) => {
+                spawn_tool(&mut commands, *item, room, center + TOOL_SPOTS[tool_count]);
+            }
+            ShopGood::Upgrade(upgrade) => {
+                upgrades.0.insert(*upgrade);
+            }
+        }
+        spawn_main_chat_box(
+            &mut commands,
+            "info",
+            format!("You bought [term]{}[/term].", entry.name),
+        );
+    }
+}
+
+fn spawn_lantern(commands: &mut Commands, room: Entity, position: Vec2) {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(0.3, 0.2, 0.1),
+                    custom_size: Some(Vec2::new(16.0, 26.0)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(0.5)),
+                ..Default::default()
+            },
+            Lantern,
+            InRoom(room),
+        ))
+        .with_children(|lantern| {
+            lantern.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(1.0, 0.8, 0.3),
+                    custom_size: Some(Vec2::new(10.0, 14.0)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(Vec3::new(0.0, 2.0, 0.1)),
+                ..Default::default()
+            });
+        });
+}
+
+// Set up like the desk's own items, minus the rig bone, so demons find, reach and use it the same.
+fn spawn_tool(commands: &mut Commands, item: DeskItem, room: Entity, position: Vec2) {
+    let mut tool = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.35, 0.3, 0.25),
+                custom_size: Some(Vec2::new(28.0, 28.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position.extend(0.5)),
+            ..Default::default()
+        },
+        item,
+        item.interactable(),
+        DeskItemState::default(),
+        InRoom(room),
+        BoughtTool,
+        Sensor,
+        ActiveEvents::COLLISION_EVENTS,
+        Collider::ball(TOOL_REACH),
+    ));
+    if item.demon_can_use() {
+        tool.insert(DemonUsable);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod assets;
+pub mod game;
+pub mod prelude;
+pub mod state;
@@ -1,22 +1,18 @@
-mod assets;
-mod game;
-mod prelude;
-mod state;
-
 use bevy::window::WindowResolution;
 use bevy_spine::SpinePlugin;
-use game::chat::MainChatAttach;
 
-use crate::assets::GameAssetsPlugin;
-use crate::game::DeskPlugin;
-use crate::prelude::*;
+use LudumDare56::assets::GameAssetsPlugin;
+use LudumDare56::game::camera::{DESIGN_HEIGHT, DESIGN_WIDTH};
+use LudumDare56::game::DeskPlugin;
+use LudumDare56::prelude::*;
 
 fn main() -> AppExit {
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Demons On My Desk".to_string(),
-                resolution: WindowResolution::new(948., 533.),
+                resolution: WindowResolution::new(DESIGN_WIDTH, DESIGN_HEIGHT),
+                resizable: true,
                 ..Default::default()
             }),
             ..Default::default()